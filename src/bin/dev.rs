@@ -4,7 +4,7 @@
 
 use donna::{
     create_alias_group, create_project, define_project_type, env_setup::handle_args,
-    update_alias_group, XDG,
+    update_alias_group, ScriptArgs, XDG,
 };
 
 fn main() {
@@ -17,15 +17,27 @@ fn main() {
     define_project_type(
         "test",
         Some(vec!["test".to_string()]),
-        Some("/Users/levirogalla/Projects/lib/cli-project-manager/lua/builder.lua"),
+        Some(vec![
+            "/Users/levirogalla/Projects/lib/cli-project-manager/lua/builder.lua".to_string(),
+        ]),
         Some("/Users/levirogalla/Projects/lib/cli-project-manager/lua/opener.lua"),
         false,
         &xdg,
     )
     .unwrap();
     // // create_lib("lib", "./test_root/lib", true, &xdg);
-    create_project("testproj", Some("test"), None, None, false, None, &xdg).unwrap();
-    // open_project("testproj", None, &xdg);
+    create_project(
+        "testproj",
+        Some("test"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    // open_project("testproj", None, &ScriptArgs::default(), &xdg);
 
     update_alias_group("test", Some("test2"), Some("./test_root/newtest"), &xdg).unwrap();
 
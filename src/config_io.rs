@@ -1,12 +1,22 @@
-use super::utils::{types, XDG};
-use crate::errors::{ConfigError, ProjectConfigError};
+use super::utils::{atomic_write, escape_path, expand_path, types, FileLock, XDG};
+use crate::config_layers::{self, ConfigLayers};
+use crate::errors::{
+    ConfigError, ConfigValueError, InvalidConfigValueError, PathVarNotDefinedError,
+    ProjectConfigError,
+};
+use crate::migrations;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::{collections::HashMap, fs};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{collections::HashMap, env, fmt, fs};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Config {
+    version: Option<u32>,
+    include: Option<Vec<String>>,
     default_lib: Option<types::LibraryName>,
+    /// Library `donna archive` moves projects to
+    archive_lib: Option<types::LibraryName>,
     library_paths: Option<HashMap<types::LibraryName, String>>,
     alias_groups: Option<HashMap<types::AliasGroupName, AliasGroup>>,
     project_types: Option<HashMap<types::ProjectTypeName, ProjectType>>,
@@ -16,41 +26,698 @@ pub struct Config {
     builders_opener: Option<String>,
     openers_opener: Option<String>,
     config_opener: Option<String>,
+
+    /// Lua script run before every builder and opener
+    lua_init: Option<String>,
+
+    relative_links: Option<bool>,
+
+    library_defaults: Option<HashMap<types::LibraryName, LibraryDefaults>>,
+
+    ignore_patterns: Option<Vec<String>>,
+
+    /// Variables that can be used in paths as `$name` or `${name}`
+    variables: Option<HashMap<String, String>>,
+
+    #[serde(skip)]
+    layers: ConfigLayers,
+    /// Form each expanded path was written in, so it can be saved the same way
+    #[serde(skip)]
+    portable_paths: BTreeMap<Vec<String>, (String, String)>,
+    /// Path, version and contents of a file that was migrated in memory when it was loaded, the original is
+    /// backed up when the migrated config is saved over it
+    #[serde(skip)]
+    migrated_from: Option<(PathBuf, u32, String)>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AliasGroup {
     pub path: String,
+    /// Overrides the config level `relative_links` option for this group
+    pub relative_links: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ProjectType {
     pub default_alias_groups: Option<Vec<types::AliasGroupName>>,
-    pub builder: Option<String>,
+    pub builder: Option<Builder>,
+    pub opener: Option<String>,
+    /// How the builder is run, detected from the script if not set
+    pub builder_kind: Option<ScriptKind>,
+    /// How the opener is run, detected from the script if not set
+    pub opener_kind: Option<ScriptKind>,
+    /// Hook run before a project of the type is deleted, an error cancels the delete. When a library is deleted,
+    /// the hooks of projects that ran before the failing one are not undone
+    pub on_delete: Option<String>,
+    /// Hook run before a project of the type is moved, an error cancels the move. When a library is moved, the
+    /// hooks of projects that ran before the failing one are not undone
+    pub on_move: Option<String>,
+    /// Hook run before a project of the type is renamed, an error cancels the rename
+    pub on_rename: Option<String>,
+    /// Hook run before a project of the type is moved to the archive library, an error cancels the archiving
+    pub on_archive: Option<String>,
+    /// Hook run after the opener of a project of the type exits
+    pub on_close: Option<String>,
+    /// Hook run before a project of the type is added to an alias group, an error cancels it
+    pub on_add_to_group: Option<String>,
+}
+
+/// A builder, either one script or a list of scripts that are run in order as steps
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Builder {
+    Script(String),
+    Steps(Vec<String>),
+}
+
+impl Builder {
+    /// Create a builder from its steps, a single step is saved as a plain path
+    pub fn new(mut steps: Vec<String>) -> Builder {
+        match steps.len() {
+            1 => Builder::Script(steps.remove(0)),
+            _ => Builder::Steps(steps),
+        }
+    }
+
+    /// Get the scripts of the builder in the order they are run
+    pub fn steps(&self) -> &[String] {
+        match self {
+            Builder::Script(script) => std::slice::from_ref(script),
+            Builder::Steps(steps) => steps,
+        }
+    }
+
+    pub fn steps_mut(&mut self) -> &mut [String] {
+        match self {
+            Builder::Script(script) => std::slice::from_mut(script),
+            Builder::Steps(steps) => steps,
+        }
+    }
+}
+
+impl fmt::Display for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.steps().join(", "))
+    }
+}
+
+/// An operation on a project that the project's type can run a hook script before, or after for `Close`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectHook {
+    Delete,
+    Move,
+    Rename,
+    Archive,
+    AddToGroup,
+    Close,
+}
+
+impl ProjectHook {
+    /// Key of the hook in a project type
+    pub fn key(&self) -> &'static str {
+        match self {
+            ProjectHook::Delete => "on_delete",
+            ProjectHook::Move => "on_move",
+            ProjectHook::Rename => "on_rename",
+            ProjectHook::Archive => "on_archive",
+            ProjectHook::AddToGroup => "on_add_to_group",
+            ProjectHook::Close => "on_close",
+        }
+    }
+}
+
+/// How a builder, opener or hook script is run
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptKind {
+    /// Loaded into a Lua state with the `donna` and `PM` tables
+    Lua,
+    /// Run as a subprocess in the project directory, see `executables`
+    Executable,
+}
+
+/// Type of the value of a config key, see `Config::get_key_kind`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigValueKind {
+    String,
+    Bool,
+    StringList,
+    /// A path, relative paths are relative to the current directory
+    Path,
+    /// A builder or hook script, relative paths are relative to `builders_dir`
+    Builder,
+    /// A builder that can be a list of scripts run in order, relative paths are relative to `builders_dir`
+    BuilderSteps,
+    /// An opener script, relative paths are relative to `openers_dir`
+    Opener,
+}
+
+/// Defaults applied to projects created in or imported into a library
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct LibraryDefaults {
+    pub project_type: Option<types::ProjectTypeName>,
+    pub default_alias_groups: Option<Vec<types::AliasGroupName>>,
+    pub builder: Option<Builder>,
     pub opener: Option<String>,
+    pub builder_kind: Option<ScriptKind>,
+    pub opener_kind: Option<ScriptKind>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ProjectConfig {
+    pub version: Option<u32>,
     pub project_type: Option<types::ProjectTypeName>,
     pub opener: Option<String>,
-    pub builder: Option<String>,
+    pub builder: Option<Builder>,
+    pub builder_kind: Option<ScriptKind>,
+    pub opener_kind: Option<ScriptKind>,
     pub tracked_alias_groups: Option<Vec<types::AliasGroupName>>,
+    /// Free-form values about the project, e.g. a description or tags, available to scripts as `PM.metadata`
+    pub metadata: Option<toml::Table>,
 }
 
 impl Config {
     const RC_REL_PATH: &'static str = "project_manager/config.toml";
     const REL_DATA_DIR: &'static str = "project_manager";
+    const LOCK_FILE_NAME: &'static str = "config.lock";
+    const CONFIG_FILE_NAME: &'static str = "config.toml";
+    const PROFILES_DIR_NAME: &'static str = "profiles";
+    /// File in the root of a library with one ignore pattern per line
+    pub const LIBRARY_IGNORE_FILE_NAME: &'static str = ".donnaignore";
+    /// Patterns that are always ignored when scanning a library
+    pub const DEFAULT_IGNORE_PATTERNS: [&'static str; 3] = [".git", ".DS_Store", ".Trash*"];
 
+    /// Directory next to the builders and openers directories that `require` finds Lua modules in
+    pub const LUA_MODULES_DIR_NAME: &'static str = "lua";
+
+    const KEYS: [&'static str; 17] = [
+        "version",
+        config_layers::INCLUDE_KEY,
+        "default_lib",
+        "archive_lib",
+        "library_paths",
+        "alias_groups",
+        "project_types",
+        "builders_dir",
+        "openers_dir",
+        "builders_opener",
+        "openers_opener",
+        "config_opener",
+        "lua_init",
+        "relative_links",
+        "library_defaults",
+        "ignore_patterns",
+        "variables",
+    ];
+    const ALIAS_GROUP_KEYS: [&'static str; 2] = ["path", "relative_links"];
+    const PROJECT_TYPE_KEYS: [&'static str; 11] = [
+        "default_alias_groups",
+        "builder",
+        "opener",
+        "builder_kind",
+        "opener_kind",
+        "on_delete",
+        "on_move",
+        "on_rename",
+        "on_archive",
+        "on_add_to_group",
+        "on_close",
+    ];
+    const LIBRARY_DEFAULTS_KEYS: [&'static str; 6] = [
+        "project_type",
+        "default_alias_groups",
+        "builder",
+        "opener",
+        "builder_kind",
+        "opener_kind",
+    ];
+
+    /// Old configs are migrated in memory, the file is only rewritten when the config is saved or by `donna migrate`
     // use dependency injection for xdg to allow for parellel testing (multiple instances of XDG and home env var names)
     pub fn load(path: Option<&str>, xdg: &XDG) -> Result<Config, ConfigError> {
-        let contents = fs::read_to_string(
-            path.map(PathBuf::from)
-                .unwrap_or_else(|| Self::get_path(xdg)),
-        )?;
+        let path = path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Self::get_path(xdg));
+        let contents = Self::read_file(&path)?;
+        let parse_error = |e| ConfigError::TomlLoad(path.clone(), Box::new(e));
+
+        let mut table: toml::Table = toml::from_str(&contents).map_err(parse_error)?;
+        let version = migrations::get_version(&table)?;
+        let changes = migrations::migrate_config(&mut table, version)
+            .map_err(ConfigError::UnsupportedVersion)?;
+        if changes.is_empty() {
+            // deserialize the original contents if nothing changed so errors point to the right line
+            return Self::from_user_table(&path, table, Some(&contents), xdg);
+        }
+        for change in &changes {
+            log::info!("Migrated {} in memory: {change}", path.display());
+        }
+        let mut config = Self::from_user_table(&path, table, None, xdg)?;
+        config.migrated_from = Some((path, version, contents));
+        Ok(config)
+    }
 
-        let mut config: Config = toml::from_str(&contents)?;
+    /// Upgrade a config file to the current version and write it back, the original is backed up first. Hold the
+    /// config lock while calling this. Returns the changes, nothing is written if there are none.
+    pub fn migrate_file(path: &Path) -> Result<Vec<String>, ConfigError> {
+        let contents = Self::read_file(path)?;
+        let mut table: toml::Table = toml::from_str(&contents)
+            .map_err(|e| ConfigError::TomlLoad(path.to_path_buf(), Box::new(e)))?;
+        let version = migrations::get_version(&table)?;
+        let changes = migrations::migrate_config(&mut table, version)
+            .map_err(ConfigError::UnsupportedVersion)?;
+        if !changes.is_empty() {
+            migrations::write_migrated(path, &contents, version, &table)?;
+        }
+        Ok(changes)
+    }
+
+    /// Merge the shared config files under the user's config and expand the variables in paths
+    fn from_user_table(
+        path: &Path,
+        table: toml::Table,
+        contents: Option<&str>,
+        xdg: &XDG,
+    ) -> Result<Config, ConfigError> {
+        let parse_error = |e| ConfigError::TomlLoad(path.to_path_buf(), Box::new(e));
+        let include_vars = Self::get_path_vars(&table, xdg)?;
+        let (layers, mut merged) = ConfigLayers::load(path, table, |include| {
+            Self::expand_vars(include, &include_vars, xdg)
+                .map_err(|name| PathVarNotDefinedError(format!("{name} in include")).into())
+        })?;
+        let vars = Self::get_path_vars(&merged, xdg)?;
+        let mut portable_paths = BTreeMap::new();
+        Self::expand_table_paths(&mut merged, &[], &vars, xdg, &mut portable_paths)?;
+
+        let mut config: Config = match contents {
+            Some(contents) if layers.is_empty() && portable_paths.is_empty() => {
+                toml::from_str(contents).map_err(parse_error)?
+            }
+            _ => merged.try_into().map_err(parse_error)?,
+        };
+        config.layers = layers;
+        config.portable_paths = portable_paths;
+        Ok(config.with_defaults(xdg))
+    }
 
+    /// Variables that are always defined, `HOME` and the XDG directories follow the `XDG` passed in
+    fn get_builtin_var(name: &str, xdg: &XDG) -> Option<String> {
+        match name {
+            "HOME" => env::var(&xdg.home_var_name).ok(),
+            "XDG_CONFIG_HOME" => Some(xdg.get_config_home()),
+            "XDG_DATA_HOME" => Some(xdg.get_data_home()),
+            _ => env::var(name).ok(),
+        }
+    }
+
+    /// Get the variables defined in the `variables` table of a config, with the variables in their values expanded
+    fn get_path_vars(
+        table: &toml::Table,
+        xdg: &XDG,
+    ) -> Result<HashMap<String, String>, ConfigError> {
+        let mut vars = HashMap::new();
+        let defined = table.get("variables").and_then(toml::Value::as_table);
+        for (name, value) in defined.into_iter().flatten() {
+            let Some(value) = value.as_str() else {
+                continue;
+            };
+            let expanded = expand_path(value, |var| Self::get_builtin_var(var, xdg))
+                .map_err(|var| PathVarNotDefinedError(format!("{var} in variables.{name}")))?;
+            vars.insert(name.clone(), expanded);
+        }
+        Ok(vars)
+    }
+
+    fn expand_vars(
+        path: &str,
+        vars: &HashMap<String, String>,
+        xdg: &XDG,
+    ) -> Result<String, String> {
+        expand_path(path, |name| {
+            vars.get(name)
+                .cloned()
+                .or_else(|| Self::get_builtin_var(name, xdg))
+        })
+    }
+
+    /// Expand the variables in every path of a config table and record the form each changed path was written in
+    fn expand_table_paths(
+        table: &mut toml::Table,
+        prefix: &[String],
+        vars: &HashMap<String, String>,
+        xdg: &XDG,
+        portable_paths: &mut BTreeMap<Vec<String>, (String, String)>,
+    ) -> Result<(), ConfigError> {
+        for (key, value) in table.iter_mut() {
+            let mut path = prefix.to_vec();
+            path.push(key.clone());
+            match value {
+                toml::Value::Table(table) => {
+                    Self::expand_table_paths(table, &path, vars, xdg, portable_paths)?
+                }
+                toml::Value::String(value) if Self::is_path_key(&path) => {
+                    let expanded = Self::expand_vars(value, vars, xdg).map_err(|name| {
+                        PathVarNotDefinedError(format!("{name} in {}", path.join(".")))
+                    })?;
+                    if expanded != *value {
+                        portable_paths.insert(path, (value.clone(), expanded.clone()));
+                        *value = expanded;
+                    }
+                }
+                toml::Value::Array(values) if Self::is_path_key(&path) => {
+                    for (i, value) in values.iter_mut().enumerate() {
+                        let toml::Value::String(value) = value else {
+                            continue;
+                        };
+                        let mut path = path.clone();
+                        path.push(i.to_string());
+                        let expanded = Self::expand_vars(value, vars, xdg).map_err(|name| {
+                            PathVarNotDefinedError(format!("{name} in {}", path.join(".")))
+                        })?;
+                        if expanded != *value {
+                            portable_paths.insert(path, (value.clone(), expanded.clone()));
+                            *value = expanded;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the paths of a config table the way they are read back: in the form they were loaded in if they
+    /// haven't changed, otherwise with `$` and a leading `~` escaped so they aren't expanded on the next load
+    fn unexpand_table_paths(&self, table: &mut toml::Table, prefix: &[String]) {
+        let unexpand = |path: Vec<String>, value: &mut String| match self.portable_paths.get(&path)
+        {
+            Some((portable, expanded)) if expanded == value => *value = portable.clone(),
+            _ => *value = escape_path(value),
+        };
+        for (key, value) in table.iter_mut() {
+            let mut path = prefix.to_vec();
+            path.push(key.clone());
+            match value {
+                toml::Value::Table(table) => self.unexpand_table_paths(table, &path),
+                toml::Value::String(value) if Self::is_path_key(&path) => unexpand(path, value),
+                // paths in lists, like the steps of a builder, have their index as the last part of the key
+                toml::Value::Array(values) if Self::is_path_key(&path) => {
+                    for (i, value) in values.iter_mut().enumerate() {
+                        if let toml::Value::String(value) = value {
+                            let mut path = path.clone();
+                            path.push(i.to_string());
+                            unexpand(path, value);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn is_path_key(key: &[String]) -> bool {
+        matches!(
+            Self::get_key_kind(key),
+            Some(
+                ConfigValueKind::Path
+                    | ConfigValueKind::Builder
+                    | ConfigValueKind::BuilderSteps
+                    | ConfigValueKind::Opener
+            )
+        )
+    }
+
+    /// Read the user's own config, without the shared files, with paths under the home and XDG directories written
+    /// with `~` and variables so it can be used on another machine.
+    pub fn load_portable(xdg: &XDG) -> Result<toml::Table, ConfigError> {
+        let path = Self::get_path(xdg);
+        let mut table: toml::Table = toml::from_str(&Self::read_file(&path)?)
+            .map_err(|e| ConfigError::TomlLoad(path, Box::new(e)))?;
+        Self::make_table_portable(&mut table, &[], xdg);
+        Ok(table)
+    }
+
+    fn make_table_portable(table: &mut toml::Table, prefix: &[String], xdg: &XDG) {
+        for (key, value) in table.iter_mut() {
+            let mut path = prefix.to_vec();
+            path.push(key.clone());
+            match value {
+                toml::Value::Table(table) => Self::make_table_portable(table, &path, xdg),
+                toml::Value::String(value) if Self::is_path_key(&path) => {
+                    *value = Self::to_portable_path(value, xdg);
+                }
+                toml::Value::Array(values) if Self::is_path_key(&path) => {
+                    for value in values.iter_mut() {
+                        if let toml::Value::String(value) = value {
+                            *value = Self::to_portable_path(value, xdg);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Write a path under the XDG data or config directory or the home directory with `$XDG_DATA_HOME`,
+    /// `$XDG_CONFIG_HOME` or `~`, paths that already use variables are left as they are.
+    pub fn to_portable_path(path: &str, xdg: &XDG) -> String {
+        let dirs = [
+            ("$XDG_DATA_HOME", Some(xdg.get_data_home())),
+            ("$XDG_CONFIG_HOME", Some(xdg.get_config_home())),
+            ("~", env::var(&xdg.home_var_name).ok()),
+        ];
+        for (var, dir) in dirs {
+            let Some(rest) = dir.and_then(|dir| Path::new(path).strip_prefix(dir).ok()) else {
+                continue;
+            };
+            return if rest.as_os_str().is_empty() {
+                var.to_string()
+            } else {
+                format!("{var}/{}", rest.display())
+            };
+        }
+        path.to_string()
+    }
+
+    /// Expand the variables in a path the way paths in the config are expanded, see `expand_path`
+    pub fn expand_path(&self, path: &str, xdg: &XDG) -> Result<String, ConfigError> {
+        let mut vars = HashMap::new();
+        for (name, value) in self.variables.iter().flatten() {
+            let expanded = expand_path(value, |var| Self::get_builtin_var(var, xdg))
+                .map_err(|var| PathVarNotDefinedError(format!("{var} in variables.{name}")))?;
+            vars.insert(name.clone(), expanded);
+        }
+        Ok(Self::expand_vars(path, &vars, xdg)
+            .map_err(|name| PathVarNotDefinedError(format!("{name} in {path}")))?)
+    }
+
+    /// Save a path set to its expanded form in its portable form, as long as it isn't changed
+    pub fn keep_portable_path(&mut self, key: &[String], portable: &str, expanded: &str) {
+        if portable != expanded {
+            self.portable_paths
+                .insert(key.to_vec(), (portable.to_string(), expanded.to_string()));
+        }
+    }
+
+    /// Read a config file, a missing file means donna hasn't been set up
+    fn read_file(path: &Path) -> Result<String, ConfigError> {
+        fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ConfigError::NotInitialized(path.to_path_buf()),
+            _ => e.into(),
+        })
+    }
+
+    /// Read a config and upgrade it to the current version in memory, without writing anything.
+    /// Returns the config and the changes a migration would make.
+    pub fn load_unmigrated(path: &Path, xdg: &XDG) -> Result<(Config, Vec<String>), ConfigError> {
+        let mut table: toml::Table = toml::from_str(&Self::read_file(path)?)
+            .map_err(|e| ConfigError::TomlLoad(path.to_path_buf(), Box::new(e)))?;
+        let version = migrations::get_version(&table)?;
+        let changes = migrations::migrate_config(&mut table, version)
+            .map_err(ConfigError::UnsupportedVersion)?;
+        Ok((Self::from_user_table(path, table, None, xdg)?, changes))
+    }
+
+    /// Get the type of the value of a config key that can be set, e.g. `["project_types", "rust", "builder"]`.
+    /// None if the key can't be set.
+    pub fn get_key_kind(key: &[String]) -> Option<ConfigValueKind> {
+        let key: Vec<&str> = key.iter().map(String::as_str).collect();
+        let kind = match key.as_slice() {
+            ["default_lib" | "archive_lib"] => ConfigValueKind::String,
+            ["builders_dir" | "openers_dir"] => ConfigValueKind::Path,
+            ["builders_opener" | "openers_opener" | "config_opener"] => ConfigValueKind::Path,
+            ["lua_init"] => ConfigValueKind::Path,
+            ["relative_links"] => ConfigValueKind::Bool,
+            ["include" | "ignore_patterns"] => ConfigValueKind::StringList,
+            ["library_paths", _] => ConfigValueKind::Path,
+            ["alias_groups", _, "path"] => ConfigValueKind::Path,
+            ["alias_groups", _, "relative_links"] => ConfigValueKind::Bool,
+            ["project_types" | "library_defaults", _, "default_alias_groups"] => {
+                ConfigValueKind::StringList
+            }
+            ["project_types" | "library_defaults", _, "builder"] => ConfigValueKind::BuilderSteps,
+            ["project_types" | "library_defaults", _, "opener"] => ConfigValueKind::Opener,
+            ["project_types" | "library_defaults", _, "builder_kind" | "opener_kind"] => {
+                ConfigValueKind::String
+            }
+            ["project_types", _, "on_delete" | "on_move" | "on_rename" | "on_archive" | "on_add_to_group"
+            | "on_close"] => ConfigValueKind::Builder,
+            ["library_defaults", _, "project_type"] => ConfigValueKind::String,
+            ["variables", _] => ConfigValueKind::String,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Get every key of the config that can be set, entries of libraries, alias groups, project types and library
+    /// defaults are listed for the ones that exist.
+    pub fn get_keys(&self) -> Vec<Vec<String>> {
+        let key = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let mut keys: Vec<Vec<String>> = [
+            "default_lib",
+            "archive_lib",
+            "builders_dir",
+            "openers_dir",
+            "builders_opener",
+            "openers_opener",
+            "config_opener",
+            "lua_init",
+            "relative_links",
+            config_layers::INCLUDE_KEY,
+            "ignore_patterns",
+        ]
+        .iter()
+        .map(|name| key(&[name]))
+        .collect();
+        for name in self.library_paths.iter().flat_map(HashMap::keys) {
+            keys.push(key(&["library_paths", name]));
+        }
+        for name in self.variables.iter().flat_map(HashMap::keys) {
+            keys.push(key(&["variables", name]));
+        }
+        for name in self.alias_groups.iter().flat_map(HashMap::keys) {
+            for field in Self::ALIAS_GROUP_KEYS {
+                keys.push(key(&["alias_groups", name, field]));
+            }
+        }
+        for name in self.project_types.iter().flat_map(HashMap::keys) {
+            for field in Self::PROJECT_TYPE_KEYS {
+                keys.push(key(&["project_types", name, field]));
+            }
+        }
+        for name in self.library_defaults.iter().flat_map(HashMap::keys) {
+            for field in Self::LIBRARY_DEFAULTS_KEYS {
+                keys.push(key(&["library_defaults", name, field]));
+            }
+        }
+        keys.sort();
+        keys
+    }
+
+    /// Get the value of a key, or of a whole table, e.g. `["project_types", "rust"]`
+    pub fn get_value(&self, key: &[String]) -> Result<Option<toml::Value>, ConfigError> {
+        let mut value = toml::Value::Table(toml::Table::try_from(self)?);
+        for part in key {
+            match value.as_table_mut().and_then(|table| table.remove(part)) {
+                Some(next) => value = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// Set the value of a key, or remove it if the value is None. The config is checked against its types, tables
+    /// that don't exist yet are created.
+    pub fn set_value(
+        &mut self,
+        key: &[String],
+        value: Option<toml::Value>,
+    ) -> Result<(), ConfigValueError> {
+        let mut table = toml::Table::try_from(&*self).map_err(ConfigError::from)?;
+        let Some((last, parents)) = key.split_last() else {
+            return Ok(());
+        };
+        let mut parent = &mut table;
+        for part in parents {
+            let entry = parent
+                .entry(part.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            parent = match entry {
+                toml::Value::Table(table) => table,
+                _ => return Ok(()),
+            };
+        }
+        match value {
+            Some(value) => {
+                parent.insert(last.clone(), value);
+            }
+            None => {
+                parent.remove(last);
+            }
+        }
+
+        let mut config: Config = table.try_into().map_err(|e: toml::de::Error| {
+            InvalidConfigValueError(format!("{}: {}", key.join("."), e.message()))
+        })?;
+        config.layers = std::mem::take(&mut self.layers);
+        config.portable_paths = std::mem::take(&mut self.portable_paths);
+        *self = config;
+        Ok(())
+    }
+
+    /// Get the config file a value was set by, None for values that weren't set by any file and use the default.
+    pub fn get_origin(&self, key: &[String]) -> Option<&Path> {
+        self.layers.get_origin(key)
+    }
+
+    /// Find keys in a config file that donna doesn't know about, usually typos. Each key is returned as a path
+    /// from the root of the file, e.g. `["project_types", "rust", "bulider"]`.
+    pub fn find_unknown_keys(table: &toml::Table) -> Vec<Vec<String>> {
+        let mut unknown = Vec::new();
+        for (key, value) in table {
+            if !Self::KEYS.contains(&key.as_str()) {
+                unknown.push(vec![key.clone()]);
+                continue;
+            }
+            let known_keys: &[&str] = match key.as_str() {
+                "alias_groups" => &Self::ALIAS_GROUP_KEYS,
+                "project_types" => &Self::PROJECT_TYPE_KEYS,
+                "library_defaults" => &Self::LIBRARY_DEFAULTS_KEYS,
+                _ => continue,
+            };
+            let Some(entries) = value.as_table() else {
+                continue;
+            };
+            for (name, entry) in entries {
+                for entry_key in entry.as_table().into_iter().flat_map(|t| t.keys()) {
+                    if !known_keys.contains(&entry_key.as_str()) {
+                        unknown.push(vec![key.clone(), name.clone(), entry_key.clone()]);
+                    }
+                }
+            }
+        }
+        unknown
+    }
+
+    /// Find the line and column (both starting at 1) where a key is defined in the contents of a config file
+    pub fn locate_key(contents: &str, key: &[String]) -> Option<(usize, usize)> {
+        let document = toml_edit::ImDocument::parse(contents).ok()?;
+        let (last, parents) = key.split_last()?;
+        let mut table: &dyn toml_edit::TableLike = document.as_table();
+        for parent in parents {
+            table = table.get(parent)?.as_table_like()?;
+        }
+        let (found_key, _) = table.get_key_value(last)?;
+        let offset = found_key.span()?.start;
+        let before = &contents[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = offset - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        Some((line, column))
+    }
+
+    /// Fill in the values that have defaults
+    fn with_defaults(mut self, xdg: &XDG) -> Config {
+        let config = &mut self;
         config.library_paths.get_or_insert_with(HashMap::new);
         config.alias_groups.get_or_insert_with(HashMap::new);
 
@@ -61,9 +728,7 @@ impl Config {
             .unwrap()
             .entry("default".to_string())
             .or_insert_with(|| {
-                PathBuf::from(xdg.get_data_home())
-                    .join(Self::REL_DATA_DIR)
-                    .join("projects")
+                Self::get_default_lib_path(xdg)
                     .to_str()
                     .unwrap()
                     .to_string()
@@ -87,25 +752,85 @@ impl Config {
                 .to_string()
         });
 
-        Ok(config)
+        config
+            .lua_init
+            .get_or_insert_with(|| Self::get_default_lua_init(xdg));
+
+        self
     }
 
+    /// Get the path of the config file, named profiles each have their own
     pub fn get_path(xdg: &XDG) -> PathBuf {
-        PathBuf::from(xdg.get_config_home()).join(Self::RC_REL_PATH)
+        match &xdg.profile {
+            Some(profile) => Self::get_profiles_dir(xdg)
+                .join(profile)
+                .join(Self::CONFIG_FILE_NAME),
+            None => PathBuf::from(xdg.get_config_home()).join(Self::RC_REL_PATH),
+        }
+    }
+
+    /// Get the directory holding a directory with the config of each named profile
+    pub fn get_profiles_dir(xdg: &XDG) -> PathBuf {
+        PathBuf::from(xdg.get_config_home())
+            .join(Self::REL_DATA_DIR)
+            .join(Self::PROFILES_DIR_NAME)
+    }
+
+    /// Get the path of the Lua init script used when `lua_init` isn't set
+    pub fn get_default_lua_init(xdg: &XDG) -> String {
+        PathBuf::from(xdg.get_data_home())
+            .join(Self::REL_DATA_DIR)
+            .join("init.lua")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// Get the path of the library called "default", named profiles each have their own
+    pub fn get_default_lib_path(xdg: &XDG) -> PathBuf {
+        let data_dir = PathBuf::from(xdg.get_data_home()).join(Self::REL_DATA_DIR);
+        match &xdg.profile {
+            Some(profile) => data_dir
+                .join(Self::PROFILES_DIR_NAME)
+                .join(profile)
+                .join("projects"),
+            None => data_dir.join("projects"),
+        }
     }
 
     pub fn save(&self, path: Option<&str>, xdg: &XDG) -> Result<(), ConfigError> {
-        let toml_str = toml::to_string(self)?;
+        let mut table = toml::Table::try_from(self)?;
+        self.unexpand_table_paths(&mut table, &[]);
+        self.layers
+            .strip_inherited(&mut table, Self::find_unknown_keys)?;
+        let toml_str = toml::to_string(&table)?;
 
-        fs::write(
-            path.map(PathBuf::from)
-                .unwrap_or_else(|| PathBuf::from(xdg.get_config_home()).join(Self::RC_REL_PATH)),
-            toml_str,
-        )?;
+        let path = path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Self::get_path(xdg));
+        if let Some((migrated_path, version, contents)) = &self.migrated_from {
+            if *migrated_path == path {
+                migrations::back_up(&path, contents, *version)?;
+            }
+        }
+        atomic_write(&path, toml_str)?;
 
         Ok(())
     }
 
+    /// Lock the config against other donna processes, hold the lock from loading the config until it is saved
+    /// so concurrent changes aren't lost. Don't hold it while running scripts, they may call donna themselves.
+    /// A missing config means donna hasn't been set up, the lock isn't taken then.
+    pub fn lock(xdg: &XDG) -> Result<FileLock, ConfigError> {
+        let path = Self::get_path(xdg);
+        if !path.exists() {
+            return Err(ConfigError::NotInitialized(path));
+        }
+        Ok(FileLock::acquire(
+            &path.with_file_name(Self::LOCK_FILE_NAME),
+        )?)
+    }
+
     pub fn add_alias_group(&mut self, name: types::AliasGroupName, alias: &AliasGroup) {
         match self.alias_groups {
             Some(ref mut alias_groups) => {
@@ -139,6 +864,9 @@ impl Config {
     }
 
     pub fn delete_lib(&mut self, name: &str) -> Option<String> {
+        if let Some(library_defaults) = self.library_defaults.as_mut() {
+            library_defaults.remove(name);
+        }
         self.library_paths.as_mut().unwrap().remove(name)
     }
 
@@ -146,26 +874,95 @@ impl Config {
         self.default_lib = Some(name.to_string());
     }
 
+    pub fn clear_default_lib(&mut self) {
+        self.default_lib = None;
+    }
+
+    pub fn get_archive_lib(&self) -> Option<types::LibraryName> {
+        self.archive_lib.clone()
+    }
+
+    pub fn set_archive_lib(&mut self, name: Option<types::LibraryName>) {
+        self.archive_lib = name;
+    }
+
+    /// Get the library name, if name is none, it will return the name of the default library
+    pub fn get_lib_name<'a>(&'a self, name: Option<&'a str>) -> &'a str {
+        name.unwrap_or(self.default_lib.as_ref().map_or("default", |s| s.as_str()))
+    }
+
     /// Get the default library path, if name is none, it will try to return the default library path
     pub fn get_lib_path(&self, name: Option<&str>) -> Option<&str> {
         self.library_paths
             .as_ref()
             .unwrap()
-            .get(name.unwrap_or(self.default_lib.as_ref().map_or("default", |s| s.as_str())))
+            .get(self.get_lib_name(name))
             .map(|s| s.as_str())
     }
 
+    /// Create library defaults, the builder and opener are relative to the builders and openers dirs
+    pub fn new_library_defaults(
+        &self,
+        project_type: Option<types::ProjectTypeName>,
+        default_alias_groups: Option<Vec<types::AliasGroupName>>,
+        builder: Option<Vec<String>>,
+        opener: Option<&str>,
+    ) -> LibraryDefaults {
+        let opener_path_prefix = PathBuf::from(self.openers_dir.as_deref().unwrap_or(""));
+        LibraryDefaults {
+            project_type,
+            default_alias_groups,
+            builder: builder.map(|steps| self.new_builder(steps)),
+            opener: opener.map(|s| opener_path_prefix.join(s).to_str().unwrap().to_string()),
+            builder_kind: None,
+            opener_kind: None,
+        }
+    }
+
+    /// Set the defaults of a library, empty defaults are removed from the config
+    pub fn set_library_defaults(&mut self, name: types::LibraryName, defaults: LibraryDefaults) {
+        let library_defaults = self.library_defaults.get_or_insert_with(HashMap::new);
+        if defaults == LibraryDefaults::default() {
+            library_defaults.remove(&name);
+        } else {
+            library_defaults.insert(name, defaults);
+        }
+    }
+
+    /// Get the defaults of a library, if name is none, the defaults of the default library are returned
+    pub fn get_library_defaults(&self, name: Option<&str>) -> Option<&LibraryDefaults> {
+        self.library_defaults
+            .as_ref()
+            .and_then(|library_defaults| library_defaults.get(self.get_lib_name(name)))
+    }
+
+    pub fn set_ignore_patterns(&mut self, patterns: Option<Vec<String>>) {
+        self.ignore_patterns = patterns;
+    }
+
+    /// Get the global ignore patterns, including the built in defaults
+    pub fn get_ignore_patterns(&self) -> Vec<String> {
+        Self::DEFAULT_IGNORE_PATTERNS
+            .iter()
+            .map(|s| s.to_string())
+            .chain(self.ignore_patterns.clone().unwrap_or_default())
+            .collect()
+    }
+
+    pub fn get_all_library_defaults(&self) -> HashMap<types::LibraryName, LibraryDefaults> {
+        self.library_defaults.clone().unwrap_or_default()
+    }
+
     pub fn add_project_type(
         &mut self,
         name: types::ProjectTypeName,
         default_alias_groups: Option<Vec<types::AliasGroupName>>,
-        builder: Option<&str>,
+        builder: Option<Vec<String>>,
         opener: Option<&str>,
     ) {
-        let builder_path_prefix = PathBuf::from(self.builders_dir.as_deref().unwrap_or(""));
         let opener_path_prefix = PathBuf::from(self.openers_dir.as_deref().unwrap_or(""));
 
-        let builder = builder.map(|s| builder_path_prefix.join(s).to_str().unwrap().to_string());
+        let builder = builder.map(|steps| self.new_builder(steps));
         let opener = opener.map(|s| opener_path_prefix.join(s).to_str().unwrap().to_string());
 
         match self.project_types {
@@ -173,20 +970,31 @@ impl Config {
                 // lazy load alias_groups
                 project_types.insert(
                     name.to_string(),
-                    ProjectType::new(default_alias_groups, builder.as_deref(), opener.as_deref()),
+                    ProjectType::new(default_alias_groups, builder.clone(), opener.as_deref()),
                 );
             }
             None => {
                 let mut project_types = HashMap::new();
                 project_types.insert(
                     name.to_string(),
-                    ProjectType::new(default_alias_groups, builder.as_deref(), opener.as_deref()),
+                    ProjectType::new(default_alias_groups, builder, opener.as_deref()),
                 );
                 self.project_types = Some(project_types);
             }
         }
     }
 
+    /// Create a builder from its steps, relative to the builders dir
+    pub fn new_builder(&self, steps: Vec<String>) -> Builder {
+        let builder_path_prefix = PathBuf::from(self.builders_dir.as_deref().unwrap_or(""));
+        Builder::new(
+            steps
+                .iter()
+                .map(|step| builder_path_prefix.join(step).to_str().unwrap().to_string())
+                .collect(),
+        )
+    }
+
     pub fn delete_project_type(&mut self, name: &str) -> Option<ProjectType> {
         self.project_types.as_mut().unwrap().remove(name)
     }
@@ -240,12 +1048,52 @@ impl Config {
     pub fn get_builders_path_prefix(&self) -> String {
         self.builders_dir.clone().unwrap() // will never be None if the config is loaded correctly
     }
+
+    pub fn get_lua_init(&self) -> String {
+        self.lua_init.clone().unwrap() // will never be None if the config is loaded correctly
+    }
+
+    /// Get the directories `require` finds Lua modules in, the `lua` directory next to the builders directory and
+    /// the one next to the openers directory
+    pub fn get_lua_module_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = [
+            self.get_builders_path_prefix(),
+            self.get_openers_path_prefix(),
+        ]
+        .iter()
+        .map(|dir| {
+            Path::new(dir)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(Self::LUA_MODULES_DIR_NAME)
+        })
+        .collect();
+        dirs.dedup();
+        dirs
+    }
+
+    pub fn set_relative_links(&mut self, relative: Option<bool>) {
+        self.relative_links = relative;
+    }
+
+    pub fn get_relative_links(&self) -> Option<bool> {
+        self.relative_links
+    }
+
+    /// Whether aliases in the given group should be relative symlinks, the group setting takes precedence over the config setting
+    pub fn uses_relative_links(&self, alias_group: &str) -> bool {
+        self.get_alias_group(alias_group)
+            .and_then(|group| group.relative_links)
+            .or(self.relative_links)
+            .unwrap_or(false)
+    }
 }
 
 impl AliasGroup {
     pub fn new(path: &str) -> AliasGroup {
         AliasGroup {
             path: path.to_string(),
+            relative_links: None,
         }
     }
 
@@ -275,13 +1123,33 @@ impl AliasGroup {
 impl ProjectType {
     pub fn new(
         default_alias_groups: Option<Vec<String>>,
-        builder: Option<&str>,
+        builder: Option<Builder>,
         opener: Option<&str>,
     ) -> ProjectType {
         ProjectType {
             default_alias_groups,
-            builder: builder.map(|s| s.to_string()),
+            builder,
             opener: opener.map(|s| s.to_string()),
+            builder_kind: None,
+            opener_kind: None,
+            on_delete: None,
+            on_move: None,
+            on_rename: None,
+            on_archive: None,
+            on_add_to_group: None,
+            on_close: None,
+        }
+    }
+
+    /// Get the script of a hook, None if the type has no script for it
+    pub fn get_hook(&self, hook: ProjectHook) -> Option<&str> {
+        match hook {
+            ProjectHook::Delete => self.on_delete.as_deref(),
+            ProjectHook::Move => self.on_move.as_deref(),
+            ProjectHook::Rename => self.on_rename.as_deref(),
+            ProjectHook::Archive => self.on_archive.as_deref(),
+            ProjectHook::AddToGroup => self.on_add_to_group.as_deref(),
+            ProjectHook::Close => self.on_close.as_deref(),
         }
     }
 }
@@ -292,29 +1160,68 @@ impl ProjectConfig {
     pub fn new(
         project_type: Option<types::ProjectTypeName>,
         opener: Option<String>,
-        builder: Option<String>,
+        builder: Option<Builder>,
         tracked_alias_groups: Option<Vec<String>>,
     ) -> ProjectConfig {
         ProjectConfig {
+            version: Some(migrations::PROJECT_CONFIG_VERSION),
             tracked_alias_groups,
             project_type,
             opener,
             builder,
+            builder_kind: None,
+            opener_kind: None,
+            metadata: None,
         }
     }
 
+    /// Old project configs are migrated in memory, the file is only rewritten when the project config is saved or
+    /// by `donna migrate`
     pub fn load(path: &str) -> Result<ProjectConfig, ProjectConfigError> {
         let contents = fs::read_to_string(path)?;
+        let parse_error = |e| ProjectConfigError::TomlLoad(PathBuf::from(path), Box::new(e));
 
-        let config: ProjectConfig = toml::from_str(&contents)?;
+        let mut table: toml::Table = toml::from_str(&contents).map_err(parse_error)?;
+        let version = migrations::get_version(&table)?;
+        let changes = migrations::migrate_project_config(&mut table, version)
+            .map_err(ProjectConfigError::UnsupportedVersion)?;
+        if changes.is_empty() {
+            return toml::from_str(&contents).map_err(parse_error);
+        }
+        for change in &changes {
+            log::info!("Migrated {path} in memory: {change}");
+        }
+        table.try_into().map_err(parse_error)
+    }
 
-        Ok(config)
+    /// Upgrade a project config file to the current version and write it back, the original is backed up first.
+    /// Returns the changes, nothing is written if there are none.
+    pub fn migrate_file(path: &str) -> Result<Vec<String>, ProjectConfigError> {
+        let contents = fs::read_to_string(path)?;
+        let mut table: toml::Table = toml::from_str(&contents)
+            .map_err(|e| ProjectConfigError::TomlLoad(PathBuf::from(path), Box::new(e)))?;
+        let version = migrations::get_version(&table)?;
+        let changes = migrations::migrate_project_config(&mut table, version)
+            .map_err(ProjectConfigError::UnsupportedVersion)?;
+        if !changes.is_empty() {
+            migrations::write_migrated(Path::new(path), &contents, version, &table)?;
+        }
+        Ok(changes)
+    }
+
+    /// Read a project config and return the changes a migration would make, without writing anything.
+    pub fn check_migration(path: &str) -> Result<Vec<String>, ProjectConfigError> {
+        let mut table: toml::Table = toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| ProjectConfigError::TomlLoad(PathBuf::from(path), Box::new(e)))?;
+        let version = migrations::get_version(&table)?;
+        migrations::migrate_project_config(&mut table, version)
+            .map_err(ProjectConfigError::UnsupportedVersion)
     }
 
     pub fn save(&self, path: &str) -> Result<(), ProjectConfigError> {
         let toml_str = toml::to_string(self)?;
 
-        fs::write(path, toml_str)?;
+        atomic_write(Path::new(path), toml_str)?;
 
         Ok(())
     }
@@ -323,10 +1230,103 @@ impl ProjectConfig {
 impl Default for ProjectConfig {
     fn default() -> Self {
         ProjectConfig {
+            version: Some(migrations::PROJECT_CONFIG_VERSION),
             project_type: None,
             opener: None,
             tracked_alias_groups: Some(vec![]),
             builder: None,
+            builder_kind: None,
+            opener_kind: None,
+            metadata: None,
         }
     }
 }
+
+/// Settings shared by all profiles, stored next to the default config
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct ProfilesConfig {
+    /// Profile used when none is given with `--profile` or `DONNA_PROFILE`
+    pub default: Option<String>,
+}
+
+impl ProfilesConfig {
+    const REL_PATH: &'static str = "project_manager/profiles.toml";
+
+    pub fn get_path(xdg: &XDG) -> PathBuf {
+        PathBuf::from(xdg.get_config_home()).join(Self::REL_PATH)
+    }
+
+    pub fn load(xdg: &XDG) -> Result<ProfilesConfig, ConfigError> {
+        let path = Self::get_path(xdg);
+        if !path.exists() {
+            return Ok(ProfilesConfig::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        toml::from_str(&contents).map_err(|e| ConfigError::TomlLoad(path, Box::new(e)))
+    }
+
+    pub fn save(&self, xdg: &XDG) -> Result<(), ConfigError> {
+        atomic_write(&Self::get_path(xdg), toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Everything needed to set donna up on another machine, written by `donna export` and read by `donna restore`
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct StateExport {
+    pub version: u32,
+    /// The user's own config, see `Config::load_portable`
+    pub config: toml::Table,
+    /// Builder scripts by their path relative to the builders directory
+    pub builders: BTreeMap<String, ExportedScript>,
+    /// Opener scripts by their path relative to the openers directory
+    pub openers: BTreeMap<String, ExportedScript>,
+    pub projects: Vec<ExportedProject>,
+}
+
+/// A script in a `StateExport`. Text scripts that aren't executable are kept as text so the export stays readable.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ExportedScript {
+    Text(String),
+    /// An executable or a file that isn't text, e.g. a compiled builder
+    File {
+        /// The contents if they are text
+        text: Option<String>,
+        /// The contents in base64 if they aren't text
+        base64: Option<String>,
+        /// Unix permissions of the file
+        mode: Option<u32>,
+    },
+}
+
+/// A project in a `StateExport`
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ExportedProject {
+    pub name: String,
+    pub library: String,
+    /// URL of the `origin` remote if the project is a git repository
+    pub remote: Option<String>,
+    /// The project's own config, with its type, alias groups, builder and opener
+    pub config: ProjectConfig,
+}
+
+impl StateExport {
+    /// Layout version of export files
+    pub const VERSION: u32 = 2;
+
+    pub fn load(path: &Path) -> Result<StateExport, ConfigError> {
+        let contents = fs::read_to_string(path)?;
+        let state: StateExport = toml::from_str(&contents)
+            .map_err(|e| ConfigError::TomlLoad(path.to_path_buf(), Box::new(e)))?;
+        if state.version > Self::VERSION {
+            return Err(ConfigError::UnsupportedVersion(state.version));
+        }
+        Ok(state)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        atomic_write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+//! Layers shared config files under the user's own config.
+//!
+//! Files listed in the `include` key of the user's config are merged first, in the order they are listed, then
+//! every `*.toml` file in the `config.d` directory next to the config, in alphabetical order, and the user's own
+//! config last. Tables are merged key by key, any other value replaces the one from the layers before it. Paths
+//! in `include` are relative to the directory of the config, included files can't include other files.
+
+use crate::errors::ConfigError;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Key of the list of files to include in the user's config
+pub const INCLUDE_KEY: &str = "include";
+
+/// Directory next to the config whose `*.toml` files are merged under it
+pub const DROP_IN_DIR_NAME: &str = "config.d";
+
+/// Path of a value from the root of the config, e.g. `["project_types", "rust", "builder"]`
+pub type KeyPath = Vec<String>;
+
+/// The config files a config was merged from
+#[derive(Debug, Default, Clone)]
+pub struct ConfigLayers {
+    /// Everything merged from the shared files, without the user's own config
+    base: Table,
+    /// The user's own config
+    user: Table,
+    /// File each value in the merged config was set by
+    origins: BTreeMap<KeyPath, PathBuf>,
+    /// Shared file each value in `base` was set by
+    base_origins: BTreeMap<KeyPath, PathBuf>,
+}
+
+impl ConfigLayers {
+    /// Read the files layered under a user's config and merge the user's config on top.
+    /// Returns the layers and the merged config.
+    pub fn load(
+        config_path: &Path,
+        user: Table,
+        expand_include: impl Fn(&str) -> Result<String, ConfigError>,
+    ) -> Result<(ConfigLayers, Table), ConfigError> {
+        let mut layers = ConfigLayers::default();
+        for path in get_layer_paths(config_path, &user, expand_include)? {
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+            let mut table: Table = toml::from_str(&contents)
+                .map_err(|e| ConfigError::TomlLoad(path.clone(), Box::new(e)))?;
+            table.remove(INCLUDE_KEY);
+            merge(&mut layers.base, &table, &path, &mut layers.origins, &[]);
+        }
+
+        layers.base_origins = layers.origins.clone();
+        let mut merged = layers.base.clone();
+        merge(&mut merged, &user, config_path, &mut layers.origins, &[]);
+        layers.user = user;
+        Ok((layers, merged))
+    }
+
+    /// Whether the config is only the user's own file
+    pub fn is_empty(&self) -> bool {
+        self.base.is_empty()
+    }
+
+    /// Get the file a value was set by, None if it wasn't set by any file
+    pub fn get_origin(&self, key: &[String]) -> Option<&Path> {
+        self.origins.get(key).map(PathBuf::as_path)
+    }
+
+    /// Remove the values that are the same as in the shared files and weren't set by the user, so only the user's
+    /// own values are saved to their config. Fails if a value of a shared file was removed, it can only be removed
+    /// in that file. `find_unknown_keys` finds the keys of the shared files that aren't part of the config, they
+    /// are never in `table`.
+    pub fn strip_inherited(
+        &self,
+        table: &mut Table,
+        find_unknown_keys: impl Fn(&Table) -> Vec<KeyPath>,
+    ) -> Result<(), ConfigError> {
+        let unknown_keys = find_unknown_keys(&self.base);
+        strip_inherited(table, &self.base, &self.user, &[], &|path| {
+            if unknown_keys.iter().any(|key| key == path) {
+                return Ok(());
+            }
+            let origin = self
+                .base_origins
+                .iter()
+                .find(|(key, _)| key.starts_with(path))
+                .map(|(_, origin)| origin.clone())
+                .unwrap_or_default();
+            Err(ConfigError::InheritedKey(path.join("."), origin))
+        })
+    }
+}
+
+/// Get the files layered under a config, in the order they are merged
+fn get_layer_paths(
+    config_path: &Path,
+    user: &Table,
+    expand_include: impl Fn(&str) -> Result<String, ConfigError>,
+) -> Result<Vec<PathBuf>, ConfigError> {
+    let dir = config_path.parent().unwrap_or(Path::new(""));
+    let mut paths = Vec::new();
+    let includes = user.get(INCLUDE_KEY).and_then(Value::as_array);
+    for include in includes.into_iter().flatten().filter_map(Value::as_str) {
+        paths.push(dir.join(expand_include(include)?));
+    }
+
+    let drop_in_dir = dir.join(DROP_IN_DIR_NAME);
+    if drop_in_dir.is_dir() {
+        let mut drop_ins = Vec::new();
+        for entry in drop_in_dir.read_dir()? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "toml") {
+                drop_ins.push(path);
+            }
+        }
+        drop_ins.sort();
+        paths.extend(drop_ins);
+    }
+    Ok(paths)
+}
+
+/// Merge `src` into `dst` and record `origin` as the origin of every value it sets
+fn merge(
+    dst: &mut Table,
+    src: &Table,
+    origin: &Path,
+    origins: &mut BTreeMap<KeyPath, PathBuf>,
+    prefix: &[String],
+) {
+    for (key, value) in src {
+        let mut path = prefix.to_vec();
+        path.push(key.clone());
+        match (dst.get_mut(key), value) {
+            (Some(Value::Table(dst_table)), Value::Table(src_table)) => {
+                merge(dst_table, src_table, origin, origins, &path)
+            }
+            _ => {
+                origins.retain(|key, _| !key.starts_with(&path));
+                record_origins(value, origin, origins, &path);
+                dst.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn record_origins(
+    value: &Value,
+    origin: &Path,
+    origins: &mut BTreeMap<KeyPath, PathBuf>,
+    path: &[String],
+) {
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                let mut path = path.to_vec();
+                path.push(key.clone());
+                record_origins(value, origin, origins, &path);
+            }
+        }
+        _ => {
+            origins.insert(path.to_vec(), origin.to_path_buf());
+        }
+    }
+}
+
+/// `on_removed` is called with the path of each value of `base` that isn't in `table` and wasn't set by the user
+fn strip_inherited(
+    table: &mut Table,
+    base: &Table,
+    user: &Table,
+    prefix: &[String],
+    on_removed: &impl Fn(&[String]) -> Result<(), ConfigError>,
+) -> Result<(), ConfigError> {
+    let empty = Table::new();
+    for (key, base_value) in base {
+        let mut path = prefix.to_vec();
+        path.push(key.clone());
+        let user_value = user.get(key);
+        match (table.get_mut(key), base_value) {
+            // removing the user's own value brings back the shared one
+            (None, _) if user_value.is_some() => {}
+            (None, _) => on_removed(&path)?,
+            (Some(Value::Table(table_value)), Value::Table(base_table)) => {
+                let user_table = user_value.and_then(Value::as_table).unwrap_or(&empty);
+                strip_inherited(table_value, base_table, user_table, &path, on_removed)?;
+                if table_value.is_empty() && user_value.is_none() {
+                    table.remove(key);
+                }
+            }
+            (Some(value), _) if value == base_value && user_value.is_none() => {
+                table.remove(key);
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
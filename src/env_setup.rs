@@ -32,29 +32,34 @@ pub fn get_data_path(xdg: &XDG) -> String {
         .to_string()
 }
 
-pub fn setup_pm(xdg: &XDG) {
+/// Config written to a new config file, every key is commented out so the defaults apply
+pub const STARTER_CONFIG: &str = include_str!("templates/config.toml");
+
+/// Create the config and data directories and a starter config, anything that already exists is left as it is.
+pub fn setup_pm(xdg: &XDG) -> Result<(), std::io::Error> {
     let config_dir = get_config_path(xdg);
     let data_dir = get_data_path(xdg); // don't know if im gonna need this yet
     let config_file_path = Path::new(&config_dir).join("config.toml");
     let directories: [(&str, &str, FS); 3] = [
         (&config_dir, "Config Root", FS::Folder),
         (&data_dir, "Data Root", FS::Folder),
-        (config_file_path.to_str().unwrap(), "Aliases", FS::File),
+        (config_file_path.to_str().unwrap(), "Config", FS::File),
     ];
 
     for (path, name, fs_type) in directories
         .iter()
         .filter(|path| !Path::new(path.0).exists())
     {
-        let result = match fs_type {
-            FS::Folder => fs::create_dir_all(path).map(|_| ()),
-            FS::File => fs::File::create(path).map(|_| ()),
-        };
-        match result {
-            Ok(_) => log::info!("{name} path created at: {path}"),
-            Err(e) => panic!("Error creating {name}: {e}"),
+        match fs_type {
+            FS::Folder => fs::create_dir_all(path),
+            FS::File => fs::write(path, STARTER_CONFIG),
         }
+        .map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Error creating {name} at {path}: {e}"))
+        })?;
+        log::info!("{name} path created at: {path}");
     }
+    Ok(())
 }
 
 fn reset_test_root() {
@@ -84,12 +89,17 @@ pub fn handle_args() {
             process::exit(0);
         }
         Some("setup") => {
-            setup_pm(&XDG::new(None, None, None));
+            if let Err(e) = setup_pm(&XDG::new(None, None, None)) {
+                log::error!("{e}");
+                process::exit(1);
+            }
             process::exit(0);
         }
         Some("clean") => {
             reset_test_root();
-            setup_pm(&XDG::new(None, None, None));
+            if let Err(e) = setup_pm(&XDG::new(None, None, None)) {
+                log::error!("{e}");
+            }
         }
         _ => {}
     }
@@ -70,6 +70,28 @@ pub struct ProjectTypePathDoesNotExistError(pub String);
 #[error("Path exists: {0}")]
 pub struct ProjectTypePathExistsError(pub String);
 
+// Config value errors
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid config key: {0}")]
+pub struct InvalidConfigKeyError(pub String);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid config value: {0}")]
+pub struct InvalidConfigValueError(pub String);
+
+// Profile errors
+#[derive(thiserror::Error, Debug)]
+#[error("Already exists: {0}")]
+pub struct ProfileExistsError(pub String);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Not found: {0}")]
+pub struct ProfileNotFoundError(pub String);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid profile name: {0}")]
+pub struct InvalidProfileNameError(pub String);
+
 #[derive(thiserror::Error, Debug)]
 #[error("Path not found: {0}")]
 pub struct BuilderPathNotFoundError(pub String);
@@ -82,16 +104,138 @@ pub struct OpenerPathNotFoundError(pub String);
 #[error("Config var not defined: {0}")]
 pub struct ConfigVarNotDefinedError(pub String);
 
+#[derive(thiserror::Error, Debug)]
+#[error("Variable not defined: {0}")]
+pub struct PathVarNotDefinedError(pub String);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Already set up: {0}")]
+pub struct AlreadySetUpError(pub String);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid export: {0}")]
+pub struct InvalidExportError(pub String);
+
+/// A question asked by a script with `donna.prompt`, `donna.confirm` or `donna.select` couldn't be answered
+#[derive(thiserror::Error, Debug)]
+#[error("Prompt failed: {0}")]
+pub struct PromptError(pub String);
+
+/// A builder script raised an error or failed to compile
+#[derive(thiserror::Error, Debug)]
+#[error("Builder {path} failed: {message}")]
+pub struct BuilderRuntimeError {
+    pub path: String,
+    pub message: String,
+    pub traceback: Option<String>,
+}
+
+/// An opener script raised an error or failed to compile
+#[derive(thiserror::Error, Debug)]
+#[error("Opener {path} failed: {message}")]
+pub struct OpenerRuntimeError {
+    pub path: String,
+    pub message: String,
+    pub traceback: Option<String>,
+}
+
+/// A project type hook raised an error or failed to compile, the operation it was run for is cancelled
+#[derive(thiserror::Error, Debug)]
+#[error("Hook {hook} {path} failed: {message}")]
+pub struct HookRuntimeError {
+    /// Key of the hook, e.g. `on_delete`
+    pub hook: String,
+    pub path: String,
+    pub message: String,
+    pub traceback: Option<String>,
+}
+
+/// An executable builder, opener or hook couldn't be started or exited with a non-zero status
+#[derive(thiserror::Error, Debug)]
+#[error("{path} {message}")]
+pub struct ExecutableError {
+    pub path: String,
+    /// Exit code of the script, None if it couldn't be started or was killed by a signal
+    pub code: Option<i32>,
+    pub message: String,
+}
+
+/// Split the error of a Lua script into its message and stack traceback
+fn split_lua_error(error: &mlua::Error) -> (String, Option<String>) {
+    let error = error.to_string();
+    match error.split_once("stack traceback:") {
+        Some((message, traceback)) => (
+            message.trim_end().to_string(),
+            Some(format!("stack traceback:{}", traceback.trim_end())),
+        ),
+        None => (error, None),
+    }
+}
+
+impl BuilderRuntimeError {
+    pub fn new(path: &str, error: &mlua::Error) -> Self {
+        let (message, traceback) = split_lua_error(error);
+        BuilderRuntimeError {
+            path: path.to_string(),
+            message,
+            traceback,
+        }
+    }
+}
+
+impl HookRuntimeError {
+    pub fn new(hook: &str, path: &str, error: &mlua::Error) -> Self {
+        let (message, traceback) = split_lua_error(error);
+        HookRuntimeError {
+            hook: hook.to_string(),
+            path: path.to_string(),
+            message,
+            traceback,
+        }
+    }
+}
+
+impl OpenerRuntimeError {
+    pub fn new(path: &str, error: &mlua::Error) -> Self {
+        let (message, traceback) = split_lua_error(error);
+        OpenerRuntimeError {
+            path: path.to_string(),
+            message,
+            traceback,
+        }
+    }
+}
+
+/// The `version` key of a file isn't a layout version, e.g. a negative number
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid version: {0}")]
+pub struct InvalidVersionError(pub String);
+
 #[derive(thiserror::Error, Debug)]
 pub enum ConfigError {
     #[error("IO error: {0}")]
     BadPath(#[from] std::io::Error),
 
-    #[error("TOML parsing error: {0}")]
-    TomlLoad(#[from] toml::de::Error),
+    #[error("Error parsing {}:\n{}", .0.display(), .1)]
+    TomlLoad(std::path::PathBuf, #[source] Box<toml::de::Error>),
 
     #[error("TOML serialization error: {0}")]
     TomlSave(#[from] toml::ser::Error),
+
+    #[error("Config version {0} is newer than this version of donna supports")]
+    UnsupportedVersion(u32),
+
+    #[error("Config {0}")]
+    InvalidVersion(#[from] InvalidVersionError),
+
+    #[error("Error expanding path: {0}")]
+    PathVarNotDefined(#[from] PathVarNotDefinedError),
+
+    #[error("{} does not exist, run `donna init` to set donna up", .0.display())]
+    NotInitialized(std::path::PathBuf),
+
+    #[error("{} is set in {}, it can only be removed there", .0, .1.display())]
+    InheritedKey(String, std::path::PathBuf),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -99,11 +243,17 @@ pub enum ProjectConfigError {
     #[error("IO error: {0}")]
     BadPath(#[from] std::io::Error),
 
-    #[error("TOML parsing error: {0}")]
-    TomlLoad(#[from] toml::de::Error),
+    #[error("Error parsing {}:\n{}", .0.display(), .1)]
+    TomlLoad(std::path::PathBuf, #[source] Box<toml::de::Error>),
 
     #[error("TOML serialization error: {0}")]
     TomlSave(#[from] toml::ser::Error),
+
+    #[error("Project config version {0} is newer than this version of donna supports")]
+    UnsupportedVersion(u32),
+
+    #[error("Project config {0}")]
+    InvalidVersion(#[from] InvalidVersionError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -153,6 +303,22 @@ pub enum CreateLibError {
     // io error
     #[error("IO Error: {0}")]
     IoError(#[from] std::io::Error),
+
+    // project type not tracked
+    #[error("Project type not tracked: {0}")]
+    ProjectTypeNotTracked(#[from] ProjectTypeNotTrackedError),
+
+    // alias group not tracked
+    #[error("Alias group not tracked: {0}")]
+    AliasGroupNotTracked(#[from] AliasGroupNotTrackedError),
+
+    // builder path not found
+    #[error("Builder path not found: {0}")]
+    BuilderPathNotFound(#[from] BuilderPathNotFoundError),
+
+    // opener path not found
+    #[error("Opener path not found: {0}")]
+    OpenerPathNotFound(#[from] OpenerPathNotFoundError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -185,6 +351,14 @@ pub enum CreateProjectError {
     #[error("Builder path not found: {0}")]
     BuilderPathNotFound(#[from] BuilderPathNotFoundError),
 
+    // builder raised an error
+    #[error("{0}")]
+    BuilderRuntime(#[from] BuilderRuntimeError),
+
+    // an executable builder failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+
     // alias group not tracked
     #[error("Alias group not tracked: {0}")]
     AliasGroupNotTracked(#[from] AliasGroupNotTrackedError),
@@ -192,6 +366,10 @@ pub enum CreateProjectError {
     // sub process error
     #[error("Sub process error: {0}")]
     SubProcessError(#[from] SubProcessError),
+
+    // lib path does not exist
+    #[error("Lib path does not exist: {0}")]
+    LibPathDoesNotExist(#[from] LibPathDoesNotExistError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -215,6 +393,18 @@ pub enum OpenProjectError {
     // opener path not found
     #[error("Opener path not found: {0}")]
     OpenerPathNotFound(#[from] OpenerPathNotFoundError),
+
+    // opener raised an error
+    #[error("{0}")]
+    OpenerRuntime(#[from] OpenerRuntimeError),
+
+    // an executable opener or on_close hook failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+
+    // the project's on_close hook failed
+    #[error("{0}")]
+    HookRuntime(#[from] HookRuntimeError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -227,6 +417,14 @@ pub enum OpenConfigError {
     #[error("Opener path not found: {0}")]
     OpenerPathNotFound(#[from] OpenerPathNotFoundError),
 
+    // opener raised an error
+    #[error("{0}")]
+    OpenerRuntime(#[from] OpenerRuntimeError),
+
+    // an executable opener failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+
     // config var not defined
     #[error("Config var not defined: {0}")]
     ConfigVarNotDefined(#[from] ConfigVarNotDefinedError),
@@ -242,6 +440,14 @@ pub enum OpenOpenersError {
     #[error("Opener path not found: {0}")]
     OpenerPathNotFound(#[from] OpenerPathNotFoundError),
 
+    // opener raised an error
+    #[error("{0}")]
+    OpenerRuntime(#[from] OpenerRuntimeError),
+
+    // an executable opener failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+
     // config var not defined
     #[error("Config var not defined: {0}")]
     ConfigVarNotDefined(#[from] ConfigVarNotDefinedError),
@@ -257,6 +463,14 @@ pub enum OpenBuildersError {
     #[error("Opener path not found: {0}")]
     OpenerPathNotFound(#[from] OpenerPathNotFoundError),
 
+    // opener raised an error
+    #[error("{0}")]
+    OpenerRuntime(#[from] OpenerRuntimeError),
+
+    // an executable opener failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+
     // config var not defined
     #[error("Config var not defined: {0}")]
     ConfigVarNotDefined(#[from] ConfigVarNotDefinedError),
@@ -338,6 +552,192 @@ pub enum DeleteAliasGroupError {
     UntrackAliasGroupError(#[from] UntrackAliasGroupError),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum UpdateLibraryError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // lib already tracked
+    #[error("Lib already tracked: {0}")]
+    LibAlreadyTracked(#[from] LibAlreadyTrackedError),
+
+    // lib path exists
+    #[error("Lib path exists: {0}")]
+    LibPathExists(#[from] LibPathExistsError),
+
+    // move file error
+    #[error("Move file error: {0}")]
+    MoveFileError(#[from] std::io::Error),
+
+    // a project's on_move hook failed
+    #[error("{0}")]
+    HookRuntime(#[from] HookRuntimeError),
+
+    // an executable hook failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetLibraryAliasesError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DeleteLibraryError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // trash error
+    #[error("Trash error: {0}")]
+    DeleteError(#[from] DeleteError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    // a project's on_delete hook failed
+    #[error("{0}")]
+    HookRuntime(#[from] HookRuntimeError),
+
+    // an executable hook failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum UpdateProjectError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // project path does not exist
+    #[error("Project path does not exist: {0}")]
+    ProjectPathDoesNotExist(#[from] ProjectPathDoesNotExistError),
+
+    // project path exists
+    #[error("Project path exists: {0}")]
+    ProjectPathExists(#[from] ProjectPathExistsError),
+
+    // move file error
+    #[error("Move file error: {0}")]
+    MoveFileError(#[from] std::io::Error),
+
+    // a project's on_rename or on_move hook failed
+    #[error("{0}")]
+    HookRuntime(#[from] HookRuntimeError),
+
+    // an executable hook failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveProjectError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // project path does not exist
+    #[error("Project path does not exist: {0}")]
+    ProjectPathDoesNotExist(#[from] ProjectPathDoesNotExistError),
+
+    // project path exists
+    #[error("Project path exists: {0}")]
+    ProjectPathExists(#[from] ProjectPathExistsError),
+
+    // move file error
+    #[error("Move file error: {0}")]
+    MoveFileError(#[from] std::io::Error),
+
+    // a project's on_archive hook failed
+    #[error("{0}")]
+    HookRuntime(#[from] HookRuntimeError),
+
+    // an executable hook failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DeleteProjectError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // project path does not exist
+    #[error("Project path does not exist: {0}")]
+    ProjectPathDoesNotExist(#[from] ProjectPathDoesNotExistError),
+
+    // trash error
+    #[error("Trash error: {0}")]
+    DeleteError(#[from] DeleteError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    // the project's on_delete hook failed
+    #[error("{0}")]
+    HookRuntime(#[from] HookRuntimeError),
+
+    // an executable hook failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum UntrackLibError {
     // config error
@@ -399,6 +799,32 @@ pub enum GetProjectsError {
     ProjectConfigError(#[from] ProjectConfigError),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum GetLibraryDirsError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // read dir error
+    #[error("Read dir error: {0}")]
+    ReadDirError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SetIgnorePatternsError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // invalid pattern
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(#[from] glob::PatternError),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum GetLibsError {
     // config error
@@ -461,6 +887,70 @@ pub enum SetDefaultLibError {
     LibNotTracked(#[from] LibNotTrackedError),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum SetLibraryDefaultsError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // lib not tracked
+    #[error("Lib not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // project type not tracked
+    #[error("Project type not tracked: {0}")]
+    ProjectTypeNotTracked(#[from] ProjectTypeNotTrackedError),
+
+    // alias group not tracked
+    #[error("Alias group not tracked: {0}")]
+    AliasGroupNotTracked(#[from] AliasGroupNotTrackedError),
+
+    // builder path not found
+    #[error("Builder path not found: {0}")]
+    BuilderPathNotFound(#[from] BuilderPathNotFoundError),
+
+    // opener path not found
+    #[error("Opener path not found: {0}")]
+    OpenerPathNotFound(#[from] OpenerPathNotFoundError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SetRelativeLinksError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // alias group not tracked
+    #[error("Alias group not tracked: {0}")]
+    AliasGroupNotTracked(#[from] AliasGroupNotTrackedError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DoctorError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RepairAliasLinksError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // alias group not tracked
+    #[error("Alias group not tracked: {0}")]
+    AliasGroupNotTracked(#[from] AliasGroupNotTrackedError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum DeleteError {
     // trash error
@@ -472,6 +962,191 @@ pub enum DeleteError {
     IoError(#[from] std::io::Error),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum MigrateError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ValidateConfigError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CreateProfileError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // profile already exists
+    #[error("Profile already exists: {0}")]
+    ProfileExists(#[from] ProfileExistsError),
+
+    // invalid profile name
+    #[error("Invalid profile name: {0}")]
+    InvalidProfileName(#[from] InvalidProfileNameError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SwitchProfileError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // profile not found
+    #[error("Profile not found: {0}")]
+    ProfileNotFound(#[from] ProfileNotFoundError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetProfilesError {
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResolveProfileError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // profile not found
+    #[error("Profile not found: {0}")]
+    ProfileNotFound(#[from] ProfileNotFoundError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigValueError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // key that can't be read or set
+    #[error("Invalid config key: {0}")]
+    InvalidConfigKey(#[from] InvalidConfigKeyError),
+
+    // value of the wrong type or that references something that doesn't exist
+    #[error("Invalid config value: {0}")]
+    InvalidConfigValue(#[from] InvalidConfigValueError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AddToAliasGroupError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // library not tracked
+    #[error("Library not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // project does not exist
+    #[error("Project does not exist: {0}")]
+    ProjectPathDoesNotExist(#[from] ProjectPathDoesNotExistError),
+
+    // alias group not tracked
+    #[error("Alias group not tracked: {0}")]
+    AliasGroupNotTracked(#[from] AliasGroupNotTrackedError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    // the project's on_add_to_group hook failed
+    #[error("{0}")]
+    HookRuntime(#[from] HookRuntimeError),
+
+    // an executable hook failed
+    #[error("{0}")]
+    Executable(#[from] ExecutableError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum InitError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // create library error
+    #[error("Error registering library: {0}")]
+    CreateLibError(#[from] CreateLibError),
+
+    // create alias group error
+    #[error("Error registering alias group: {0}")]
+    CreateAliasGroupError(#[from] CreateAliasGroupError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // get projects error
+    #[error("Error getting projects: {0}")]
+    GetProjectsError(#[from] GetProjectsError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RestoreError {
+    // config error
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    // project config error
+    #[error("Project config error: {0}")]
+    ProjectConfigError(#[from] ProjectConfigError),
+
+    // config already tracks libraries, alias groups or project types
+    #[error("{0}")]
+    AlreadySetUp(#[from] AlreadySetUpError),
+
+    // a project name or library in the export isn't a plain name
+    #[error("{0}")]
+    InvalidExport(#[from] InvalidExportError),
+
+    // library of a project is not in the restored config
+    #[error("Library not tracked: {0}")]
+    LibNotTracked(#[from] LibNotTrackedError),
+
+    // io error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
 // #[derive(thiserror::Error, Debug)]
 // pub enum DonnaError {
 //     #[error("Config error: {0}")]
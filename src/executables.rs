@@ -0,0 +1,204 @@
+//! Builders, openers and hooks that aren't Lua scripts.
+//!
+//! Whether a script is Lua or an executable is set by the `builder_kind` and `opener_kind` fields of a project
+//! type, library defaults or project, and detected from the script otherwise: `.lua` files are Lua, files with any
+//! other extension are executables, and files without an extension are executables if they start with a shebang
+//! or are executable, Lua otherwise.
+//!
+//! Scripts that aren't executable themselves, e.g. a `setup.py` without the executable bit or any Python script on
+//! Windows, are run with the interpreter of their extension, see `INTERPRETERS`.
+//!
+//! An executable is run with the project directory, or the directory of the file it opens, as its working
+//! directory and the script arguments as its arguments. The context Lua scripts get in the `PM` table is passed as
+//! `DONNA_*` environment variables and as a JSON object on stdin:
+//!
+//! - `DONNA_ACTION`, `DONNA_VERSION` and `DONNA_PATH` – always set
+//! - `DONNA_NAME`, `DONNA_LIBRARY`, `DONNA_LIBRARY_PATH`, `DONNA_TYPE`, `DONNA_OPENER`, `DONNA_TARGET` and
+//!   `DONNA_ALIAS_GROUP` – set if the script is run for a project that has them, `DONNA_ALIAS_GROUP` is the
+//!   first alias group by name
+//!
+//! The JSON has the same keys as the `PM` table, including `alias_groups`, `builder`, `metadata`, `args`,
+//! `options`, `params` and `assume_yes`. A non-zero exit status is an error.
+
+use crate::config_io::ScriptKind;
+use crate::errors::ExecutableError;
+use crate::lua_api::ScriptContext;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Prefix of the environment variables the context is passed in
+pub const ENV_PREFIX: &str = "DONNA_";
+
+/// Interpreters scripts that aren't executable themselves are run with, by extension
+pub const INTERPRETERS: [(&str, &str); 9] = [
+    ("sh", "sh"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("py", "python3"),
+    ("rb", "ruby"),
+    ("pl", "perl"),
+    ("js", "node"),
+    ("ps1", "pwsh"),
+];
+
+/// Get how a script is run, `kind` if it's set, otherwise detected from the script. Scripts without an extension
+/// that can't be read are Lua so they fail the way a missing Lua script does.
+pub fn get_script_kind(path: &str, kind: Option<ScriptKind>) -> ScriptKind {
+    if let Some(kind) = kind {
+        return kind;
+    }
+    let path = Path::new(path);
+    match path.extension() {
+        Some(ext) if ext == "lua" => return ScriptKind::Lua,
+        Some(_) if path.exists() => return ScriptKind::Executable,
+        _ => {}
+    }
+    let mut start = [0; 2];
+    let has_shebang = fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut start))
+        .is_ok_and(|_| &start == b"#!");
+    match has_shebang || is_executable(path) {
+        true => ScriptKind::Executable,
+        false => ScriptKind::Lua,
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext == "exe" || ext == "bat" || ext == "cmd")
+}
+
+/// Run an executable script with the context and wait for it to exit.
+pub fn run_executable(path: &str, context: &ScriptContext) -> Result<(), ExecutableError> {
+    let error = |code, message: String| ExecutableError {
+        path: path.to_string(),
+        code,
+        message,
+    };
+    // the script is started in the project directory, so a relative path has to be resolved first
+    let program =
+        fs::canonicalize(path).map_err(|e| error(None, format!("couldn't be started: {e}")))?;
+    let mut command = match is_executable(&program) {
+        true => Command::new(&program),
+        false => {
+            let interpreter = program
+                .extension()
+                .and_then(|ext| INTERPRETERS.iter().find(|(known, _)| ext == *known))
+                .map(|(_, interpreter)| *interpreter)
+                .ok_or_else(|| {
+                    error(
+                        None,
+                        "isn't executable and has no known interpreter, make it executable"
+                            .to_string(),
+                    )
+                })?;
+            let mut command = Command::new(interpreter);
+            command.arg(&program);
+            command
+        }
+    };
+    let mut child = command
+        .args(&context.args.args)
+        .current_dir(context.working_dir())
+        .envs(context.env_vars())
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| error(None, format!("couldn't be started: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // scripts don't have to read the context, one that exits without reading it closes the pipe
+        let _ = stdin.write_all(context.to_json().to_string().as_bytes());
+    }
+    let status = child
+        .wait()
+        .map_err(|e| error(None, format!("couldn't be waited for: {e}")))?;
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => Err(error(Some(code), format!("exited with code {code}"))),
+        None => Err(error(None, "was terminated by a signal".to_string())),
+    }
+}
+
+impl ScriptContext {
+    /// Get the `DONNA_*` environment variables of the context, variables of unset fields are left out
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let vars = [
+            ("ACTION", Some(self.action.as_str())),
+            ("VERSION", Some(env!("CARGO_PKG_VERSION"))),
+            ("PATH", Some(self.path.as_str())),
+            ("NAME", self.name.as_deref()),
+            ("LIBRARY", self.library.as_deref()),
+            ("LIBRARY_PATH", self.library_path.as_deref()),
+            ("TYPE", self.project_type.as_deref()),
+            ("OPENER", self.opener.as_deref()),
+            ("TARGET", self.target.as_deref()),
+            (
+                "ALIAS_GROUP",
+                self.alias_groups.keys().next().map(String::as_str),
+            ),
+        ];
+        vars.into_iter()
+            .filter_map(|(name, value)| Some((format!("{ENV_PREFIX}{name}"), value?.to_string())))
+            .collect()
+    }
+
+    /// Get the context as a JSON object with the keys of the `PM` table
+    pub fn to_json(&self) -> serde_json::Value {
+        let options: serde_json::Map<String, serde_json::Value> = self
+            .args
+            .options()
+            .into_iter()
+            .map(|(key, value)| match value {
+                Some(value) => (key, serde_json::Value::String(value)),
+                None => (key, serde_json::Value::Bool(true)),
+            })
+            .collect();
+        serde_json::json!({
+            "action": self.action.as_str(),
+            "version": env!("CARGO_PKG_VERSION"),
+            "path": self.path,
+            "name": self.name,
+            "library": self.library,
+            "library_path": self.library_path,
+            "type": self.project_type,
+            "alias_groups": self.alias_groups,
+            "builder": self.builder,
+            "opener": self.opener,
+            "target": self.target,
+            "metadata": self.metadata.clone().map(|metadata| toml_to_json(toml::Value::Table(metadata))),
+            "args": self.args.args,
+            "options": options,
+            "params": self.args.params,
+            "assume_yes": self.args.assume_yes,
+        })
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(value) => serde_json::Value::String(value),
+        toml::Value::Integer(value) => serde_json::Value::from(value),
+        toml::Value::Float(value) => serde_json::Value::from(value),
+        toml::Value::Boolean(value) => serde_json::Value::Bool(value),
+        toml::Value::Datetime(value) => serde_json::Value::String(value.to_string()),
+        toml::Value::Array(values) => values.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(values) => serde_json::Value::Object(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
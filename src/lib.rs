@@ -9,25 +9,30 @@
 //!
 
 mod config_io;
+mod config_layers;
 pub mod env_setup;
 pub mod errors;
+mod executables;
+mod lua_api;
+mod migrations;
+mod prompts;
 pub mod utils; // re export for tests
 
-use config_io::ProjectType;
-use mlua::Lua;
-use std::collections::HashMap;
-
-#[cfg(unix)]
-use std::os::unix::fs::symlink;
-
-#[cfg(windows)]
-use std::os::windows::fs::symlink_dir as symlink;
-
+use base64::prelude::{Engine, BASE64_STANDARD};
+use config_io::{
+    ConfigValueKind, ExportedProject, ExportedScript, ProfilesConfig, ProjectHook, ProjectType,
+    StateExport,
+};
+use executables::{get_script_kind, run_executable};
+use lua_api::{ScriptAction, ScriptContext};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::{collections::HashSet, fs};
-use utils::{delete, to_full_path};
+use utils::{create_link, delete, resolve_link, to_full_path};
 
-pub use config_io::{AliasGroup, Config, ProjectConfig};
+pub use config_io::{AliasGroup, Builder, Config, LibraryDefaults, ProjectConfig, ScriptKind};
+pub use lua_api::ScriptArgs;
 pub use utils::XDG;
 
 mod api_types {
@@ -42,7 +47,7 @@ mod api_types {
 /// # Arguments
 /// - `name` – The unique name for the project type (e.g., "rust").
 /// - `default_alias_groups` – Optional set of alias groups to be automatically added.
-/// - `builder` – Optional command (like "cargo init") to set up the project.
+/// - `builder` – Optional builder scripts to set up the project, run in order, relative to `builders_dir`.
 /// - `opener` – Optional command (like "code") to open the project.
 /// - `xdg` – Reference to the current XDG configuration.
 ///
@@ -55,11 +60,12 @@ mod api_types {
 pub fn define_project_type(
     name: &str,
     default_alias_groups: Option<Vec<String>>,
-    builder: Option<&str>,
+    builder: Option<Vec<String>>,
     opener: Option<&str>,
     redefine: bool,
     xdg: &XDG,
 ) -> Result<(), errors::ProjectTypeDefinitionError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     if !redefine && config.get_project_type(name.to_string()).is_some() {
         Err(errors::ProjectTypeAlreadyTrackedError(format!(
@@ -88,6 +94,7 @@ pub fn create_alias_group(
     already_exists: bool,
     xdg: &XDG,
 ) -> Result<(), errors::CreateAliasGroupError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     let path = &to_full_path(path);
     if !already_exists {
@@ -121,15 +128,37 @@ pub fn create_alias_group(
 /// - `name` – The identifier for the library.
 /// - `path` – Filesystem path where projects will be stored.
 /// - `default` – Whether to set this library as the default.
+/// - `already_exists` – Whether the library directory already exists, otherwise it's created.
+/// - `defaults` – Optional defaults for projects in the library, see `set_library_defaults`. Builder and opener
+///   paths are relative to `builders_dir` and `openers_dir`. They are checked before the library is created.
 /// - `xdg` – XDG configuration reference.
 pub fn create_lib(
     name: &str,
     path: &str,
     default: bool,
     already_exists: bool,
+    defaults: Option<&LibraryDefaults>,
     xdg: &XDG,
 ) -> Result<(), errors::CreateLibError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
+    let lib_defaults = match defaults {
+        Some(defaults) => LibraryDefaults {
+            builder_kind: defaults.builder_kind,
+            opener_kind: defaults.opener_kind,
+            ..config.new_library_defaults(
+                defaults.project_type.clone(),
+                defaults.default_alias_groups.clone(),
+                defaults
+                    .builder
+                    .as_ref()
+                    .map(|builder| builder.steps().to_vec()),
+                defaults.opener.as_deref(),
+            )
+        },
+        None => LibraryDefaults::default(),
+    };
+    check_library_defaults::<errors::CreateLibError>(&config, &lib_defaults)?;
     let path = &to_full_path(path);
     if !already_exists {
         if path.exists() {
@@ -150,6 +179,7 @@ pub fn create_lib(
     if default {
         config.set_default_lib(name.to_string());
     }
+    config.set_library_defaults(name.to_string(), lib_defaults);
     config.save(None, xdg)?;
     Ok(())
 }
@@ -163,7 +193,9 @@ pub fn create_lib(
 /// - `lib` – Optional library name to store the project in.
 /// - `already_exists` – Optional flag to indicate if the project already exists. If it does, it will not call the builder and it will not create the project directory.
 /// - `git_clone` – Optional git repository URL to clone the project from. This will block the builder from running.
+/// - `args` – Extra arguments passed to the builder.
 /// - `xdg` – XDG configuration reference.
+#[allow(clippy::too_many_arguments)]
 pub fn create_project(
     name: &str,
     project_type: Option<api_types::ProjectTypeName>,
@@ -171,21 +203,31 @@ pub fn create_project(
     lib: Option<api_types::LibraryName>,
     already_exists: bool,
     git_clone: Option<&str>,
+    args: &ScriptArgs,
     xdg: &XDG,
 ) -> Result<(), errors::CreateProjectError> {
     // TODO: Allow just passing of alias location, maybe you want to make an alias not in a designated alias group, just in like a school folder for example
     let config = Config::load(None, xdg)?;
-    let project_path = Path::new(config.get_lib_path(lib).ok_or(errors::LibNotTrackedError(
+    let lib_path = Path::new(config.get_lib_path(lib).ok_or(errors::LibNotTrackedError(
         format!(
             "Library '{}' could not be found",
             lib.unwrap_or("[default]")
         ),
-    ))?)
-    .join(name);
+    ))?);
+    // don't create the project on the parent file system if the library is on an unmounted drive
+    if !lib_path.is_dir() {
+        Err(errors::LibPathDoesNotExistError(format!(
+            "Library '{}' is unavailable, {} does not exist",
+            config.get_lib_name(lib),
+            lib_path.display()
+        )))?;
+    }
+    let project_path = lib_path.join(name);
     let project_config_file_path = project_path.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
     // let project_path = project_config_path.parent().expect("Invalid project config path");
 
     let project_config_dir = project_config_file_path.parent().unwrap();
+    let mut existing_metadata = None;
 
     match (
         already_exists,
@@ -228,17 +270,27 @@ pub fn create_project(
             fs::File::create_new(&project_config_file_path)?;
         }
         (true, true, true, _) => {
-            ProjectConfig::load(project_config_file_path.to_str().unwrap())?;
+            existing_metadata =
+                ProjectConfig::load(project_config_file_path.to_str().unwrap())?.metadata;
         }
     }
 
-    let mut project_config = ProjectConfig::default();
+    let mut project_config = ProjectConfig {
+        metadata: existing_metadata,
+        ..Default::default()
+    };
 
     let mut project_alias_groups: HashSet<&str> = HashSet::new();
     if let Some(ags) = alias_groups {
         project_alias_groups.extend(ags);
     }
 
+    let lib_defaults = config
+        .get_library_defaults(lib)
+        .cloned()
+        .unwrap_or_default();
+    let project_type = project_type.or(lib_defaults.project_type.as_deref());
+
     if let Some(pt) = project_type {
         let project_type_config =
             config
@@ -250,34 +302,66 @@ pub fn create_project(
         project_config.project_type = Some(pt.to_string());
         project_config.opener = project_type_config.opener.clone();
         project_config.builder = project_type_config.builder.clone();
+        project_config.builder_kind = project_type_config.builder_kind;
+        project_config.opener_kind = project_type_config.opener_kind;
 
         if let Some(alias_groups) = &project_type_config.default_alias_groups {
             project_alias_groups.extend(alias_groups.iter().map(|s| s.as_str()));
         }
+    }
 
-        // don't run builder if git clone is specified
-        if project_type_config.builder.is_some() && git_clone.is_none() {
-            let builder = project_type_config.builder.as_ref().unwrap();
-
-            let lua = Lua::new();
-            let globals = lua.globals();
-            globals.set("PM_PROJECT_NAME", name).unwrap();
-            globals
-                .set("PM_PROJECT_PATH", project_path.to_str())
-                .unwrap();
-            globals.set("PM_PROJECT_TYPE", pt).unwrap();
-            globals.set("PM_PROJECT_LIB", lib).unwrap();
-            if !already_exists {
-                lua.load(fs::read_to_string(builder).map_err(|_| {
-                    errors::BuilderPathNotFoundError(format!(
-                        "Builder path {builder} does not exist"
-                    ))
-                })?)
-                .exec()
-                .expect("Failed to run project builder")
-            };
-            // TODO: maybe run clean up code here to delete the project dir if building it fails
+    // library overrides take precedence over the project type
+    if let Some(alias_groups) = &lib_defaults.default_alias_groups {
+        project_alias_groups.extend(alias_groups.iter().map(|s| s.as_str()));
+    }
+    if lib_defaults.builder.is_some() {
+        project_config.builder = lib_defaults.builder.clone();
+        project_config.builder_kind = lib_defaults.builder_kind;
+    }
+    if lib_defaults.opener.is_some() {
+        project_config.opener = lib_defaults.opener.clone();
+        project_config.opener_kind = lib_defaults.opener_kind;
+    }
+
+    // don't run builder if git clone is specified or the project already exists
+    if let (Some(builder), None, false) = (&project_config.builder, git_clone, already_exists) {
+        let mut sorted_alias_groups: Vec<&str> = project_alias_groups.iter().copied().collect();
+        sorted_alias_groups.sort();
+        let context = ScriptContext::for_project(
+            ScriptAction::Build,
+            name,
+            &project_path,
+            lib,
+            &project_config,
+            &sorted_alias_groups,
+            &config,
+        )
+        .with_args(args);
+        // the Lua steps share a Lua state that is created for the first one, the steps stop at the first one
+        // that fails
+        let mut lua = None;
+        let steps = builder.steps();
+        for (i, step) in steps.iter().enumerate() {
+            log::info!("Running builder step {}/{}: {step}", i + 1, steps.len());
+            if get_script_kind(step, project_config.builder_kind) == ScriptKind::Executable {
+                run_executable(step, &context)?;
+                continue;
+            }
+            if lua.is_none() {
+                lua =
+                    Some(lua_api::new_lua(&context, &config, xdg).map_err(|e| {
+                        errors::BuilderRuntimeError::new(&config.get_lua_init(), &e)
+                    })?);
+            }
+            let lua = lua.as_ref().expect("The Lua state was just created");
+            lua.load(fs::read_to_string(step).map_err(|_| {
+                errors::BuilderPathNotFoundError(format!("Builder path {step} does not exist"))
+            })?)
+            .set_name(format!("@{step}"))
+            .exec()
+            .map_err(|e| errors::BuilderRuntimeError::new(step, &e))?;
         }
+        // TODO: maybe run clean up code here to delete the project dir if building it fails
     }
 
     for alias_group in project_alias_groups {
@@ -293,22 +377,168 @@ pub fn create_project(
             .as_mut()
             .unwrap()
             .push(alias_group.to_string());
-        symlink(&project_path, alias_path)?;
+        create_link(
+            &project_path,
+            &alias_path,
+            config.uses_relative_links(alias_group),
+        )?;
     }
     project_config.save(project_config_file_path.to_str().unwrap())?;
 
     Ok(())
 }
 
-/// Opens a project by loading its configuration and executing the specified opener command.
+/// Link an existing project into an alias group and track the group in the project config. Nothing changes if
+/// the project is already in the group.
+///
+/// # Arguments
+/// - `name` – The name of the project.
+/// - `alias_group` – The alias group to link the project into.
+/// - `lib` – Optional library name to locate the project.
+/// - `xdg` – XDG configuration reference.
+pub fn add_to_alias_group(
+    name: &str,
+    alias_group: &str,
+    lib: Option<api_types::LibraryName>,
+    xdg: &XDG,
+) -> Result<(), errors::AddToAliasGroupError> {
+    let config = Config::load(None, xdg)?;
+    let project_path = Path::new(config.get_lib_path(lib).ok_or(errors::LibNotTrackedError(
+        format!("Library not found: {}", lib.unwrap_or("[default]")),
+    ))?)
+    .join(name);
+    if !project_path.exists() {
+        Err(errors::ProjectPathDoesNotExistError(format!(
+            "Project path {} does not exist",
+            project_path.display()
+        )))?;
+    }
+    let alias = config
+        .get_alias_group(alias_group)
+        .ok_or(errors::AliasGroupNotTrackedError(format!(
+            "Alias group {alias_group} does not exist"
+        )))?;
+    let project_config_path = project_path.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+    let mut project_config = ProjectConfig::load(project_config_path.to_str().unwrap())?;
+    let alias_path = Path::new(&alias.path).join(name);
+    let tracked_alias_groups = project_config
+        .tracked_alias_groups
+        .get_or_insert_with(Vec::new);
+    if tracked_alias_groups
+        .iter()
+        .any(|group| group == alias_group)
+        && fs::symlink_metadata(&alias_path).is_ok()
+    {
+        return Ok(());
+    }
+    run_project_hook::<errors::AddToAliasGroupError>(
+        ProjectHook::AddToGroup,
+        name,
+        &project_path,
+        lib,
+        Some(alias_group),
+        &config,
+        xdg,
+    )?;
+    if fs::symlink_metadata(&alias_path).is_err() {
+        create_link(
+            &project_path,
+            &alias_path,
+            config.uses_relative_links(alias_group),
+        )?;
+    }
+    if !tracked_alias_groups
+        .iter()
+        .any(|group| group == alias_group)
+    {
+        tracked_alias_groups.push(alias_group.to_string());
+    }
+    project_config.save(project_config_path.to_str().unwrap())?;
+    Ok(())
+}
+
+/// Run a hook of a project's type before an operation on the project. Projects without a project config, a type or
+/// a hook for the operation are skipped. An error in the hook or the project config cancels the operation.
+fn run_project_hook<E>(
+    hook: ProjectHook,
+    name: &str,
+    project_path: &Path,
+    lib: Option<&str>,
+    target: Option<&str>,
+    config: &Config,
+    xdg: &XDG,
+) -> Result<(), E>
+where
+    E: From<errors::HookRuntimeError>
+        + From<errors::ExecutableError>
+        + From<errors::ProjectConfigError>,
+{
+    let project_config_path = project_path.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+    if !project_config_path.exists() {
+        return Ok(());
+    }
+    let project_config = ProjectConfig::load(project_config_path.to_str().unwrap())?;
+    let Some(script) = project_config
+        .project_type
+        .clone()
+        .and_then(|project_type| config.get_project_type(project_type))
+        .and_then(|project_type| project_type.get_hook(hook))
+    else {
+        return Ok(());
+    };
+    let action = match hook {
+        ProjectHook::Delete => ScriptAction::Delete,
+        ProjectHook::Move => ScriptAction::Move,
+        ProjectHook::Rename => ScriptAction::Rename,
+        ProjectHook::Archive => ScriptAction::Archive,
+        ProjectHook::AddToGroup => ScriptAction::AddToGroup,
+        ProjectHook::Close => ScriptAction::Close,
+    };
+    let alias_groups: Vec<&str> = project_config
+        .tracked_alias_groups
+        .iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    let context = ScriptContext::for_project(
+        action,
+        name,
+        project_path,
+        lib,
+        &project_config,
+        &alias_groups,
+        config,
+    )
+    .with_target(target);
+    log::info!("Running {} hook {script} for {name}", hook.key());
+    if get_script_kind(script, None) == ScriptKind::Executable {
+        run_executable(script, &context)?;
+        return Ok(());
+    }
+    let lua = lua_api::new_lua(&context, config, xdg)
+        .map_err(|e| errors::HookRuntimeError::new(hook.key(), &config.get_lua_init(), &e))?;
+    let contents = fs::read_to_string(script).map_err(|e| {
+        errors::HookRuntimeError::new(hook.key(), script, &mlua::Error::external(e))
+    })?;
+    lua.load(contents)
+        .set_name(format!("@{script}"))
+        .exec()
+        .map_err(|e| errors::HookRuntimeError::new(hook.key(), script, &e))?;
+    Ok(())
+}
+
+/// Opens a project by loading its configuration and executing the specified opener command. The `on_close` hook
+/// of the project's type runs once the opener exits.
 ///
 /// # Arguments
 /// - `name` – The name of the project to open.
 /// - `lib` – Optional library name to locate the project.
+/// - `args` – Extra arguments passed to the opener.
 /// - `xdg` – XDG configuration reference.
 pub fn open_project(
     name: &str,
     lib: Option<&str>,
+    args: &ScriptArgs,
     xdg: &XDG,
 ) -> Result<(), errors::OpenProjectError> {
     let config = Config::load(None, xdg)?;
@@ -324,22 +554,44 @@ pub fn open_project(
         )))?;
     }
     let project_config = ProjectConfig::load(project_config_path.to_str().unwrap())?;
-    if let Some(opener) = project_config.opener {
-        let lua = Lua::new();
-        let globals = lua.globals();
-        globals.set("PM_PROJECT_NAME", name).unwrap();
-        globals
-            .set("PM_PROJECT_PATH", project_path.to_str().unwrap())
-            .unwrap();
-        globals
-            .set("PM_PROJECT_TYPE", project_config.project_type)
-            .unwrap();
-        globals.set("PM_PROJECT_LIB", lib).unwrap();
-        lua.load(fs::read_to_string(&opener).map_err(|_| {
-            errors::OpenerPathNotFoundError(format!("Opener path {} does not exist", &opener))
-        })?)
-        .exec()
-        .expect("Failed to run project opener");
+    if let Some(opener) = &project_config.opener {
+        let alias_groups: Vec<&str> = project_config
+            .tracked_alias_groups
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        let context = ScriptContext::for_project(
+            ScriptAction::Open,
+            name,
+            &project_path,
+            lib,
+            &project_config,
+            &alias_groups,
+            &config,
+        )
+        .with_args(args);
+        if get_script_kind(opener, project_config.opener_kind) == ScriptKind::Executable {
+            run_executable(opener, &context)?;
+        } else {
+            let lua = lua_api::new_lua(&context, &config, xdg)
+                .map_err(|e| errors::OpenerRuntimeError::new(&config.get_lua_init(), &e))?;
+            lua.load(fs::read_to_string(opener).map_err(|_| {
+                errors::OpenerPathNotFoundError(format!("Opener path {opener} does not exist"))
+            })?)
+            .set_name(format!("@{opener}"))
+            .exec()
+            .map_err(|e| errors::OpenerRuntimeError::new(opener, &e))?;
+        }
+        run_project_hook::<errors::OpenProjectError>(
+            ProjectHook::Close,
+            name,
+            &project_path,
+            lib,
+            None,
+            &config,
+            xdg,
+        )?;
     }
     Ok(())
 }
@@ -358,16 +610,19 @@ pub fn open_config(xdg: &XDG) -> Result<(), errors::OpenConfigError> {
             "config_opener".to_string(),
         ))?;
 
-    let lua = Lua::new();
-    let globals = lua.globals();
-    globals
-        .set("PM_CONFIG_PATH", config_path.to_str().unwrap())
-        .unwrap();
+    let context = ScriptContext::for_path(ScriptAction::OpenConfig, config_path.to_str().unwrap());
+    if get_script_kind(&opener, None) == ScriptKind::Executable {
+        run_executable(&opener, &context)?;
+        return Ok(());
+    }
+    let lua = lua_api::new_lua(&context, &config, xdg)
+        .map_err(|e| errors::OpenerRuntimeError::new(&config.get_lua_init(), &e))?;
     lua.load(fs::read_to_string(&opener).map_err(|_| {
         errors::OpenerPathNotFoundError(format!("Config opener path {} does not exist", &opener))
     })?)
+    .set_name(format!("@{opener}"))
     .exec()
-    .expect("Failed to run config opener");
+    .map_err(|e| errors::OpenerRuntimeError::new(&opener, &e))?;
 
     Ok(())
 }
@@ -385,16 +640,22 @@ pub fn open_builders(xdg: &XDG) -> Result<(), errors::OpenBuildersError> {
             "builders_opener".to_string(),
         ))?;
 
-    let lua = Lua::new();
-    let globals = lua.globals();
-    globals
-        .set("PM_BUILDERS_PATH", config.get_builders_path_prefix())
-        .unwrap();
+    let context = ScriptContext::for_path(
+        ScriptAction::OpenBuilders,
+        &config.get_builders_path_prefix(),
+    );
+    if get_script_kind(&opener, None) == ScriptKind::Executable {
+        run_executable(&opener, &context)?;
+        return Ok(());
+    }
+    let lua = lua_api::new_lua(&context, &config, xdg)
+        .map_err(|e| errors::OpenerRuntimeError::new(&config.get_lua_init(), &e))?;
     lua.load(fs::read_to_string(&opener).map_err(|_| {
         errors::OpenerPathNotFoundError(format!("Config opener path {} does not exist", &opener))
     })?)
+    .set_name(format!("@{opener}"))
     .exec()
-    .expect("Failed to run config opener");
+    .map_err(|e| errors::OpenerRuntimeError::new(&opener, &e))?;
 
     Ok(())
 }
@@ -411,16 +672,20 @@ pub fn open_openers(xdg: &XDG) -> Result<(), errors::OpenOpenersError> {
         .ok_or(errors::ConfigVarNotDefinedError(
             "openers_opener".to_string(),
         ))?;
-    let lua = Lua::new();
-    let globals = lua.globals();
-    globals
-        .set("PM_OPENERS_PATH", config.get_openers_path_prefix())
-        .unwrap();
+    let context =
+        ScriptContext::for_path(ScriptAction::OpenOpeners, &config.get_openers_path_prefix());
+    if get_script_kind(&opener, None) == ScriptKind::Executable {
+        run_executable(&opener, &context)?;
+        return Ok(());
+    }
+    let lua = lua_api::new_lua(&context, &config, xdg)
+        .map_err(|e| errors::OpenerRuntimeError::new(&config.get_lua_init(), &e))?;
     lua.load(fs::read_to_string(&opener).map_err(|_| {
         errors::OpenerPathNotFoundError(format!("Config opener path {} does not exist", &opener))
     })?)
+    .set_name(format!("@{opener}"))
     .exec()
-    .expect("Failed to run config opener");
+    .map_err(|e| errors::OpenerRuntimeError::new(&opener, &e))?;
     Ok(())
 }
 
@@ -488,28 +753,38 @@ pub fn update_alias_group(
     new_path: Option<&str>,
     xdg: &XDG,
 ) -> Result<(), errors::UpdateAliasGroupError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     let new_path = new_path.map(to_full_path);
-    let alias = config
+    let mut alias = config
         .delete_alias_group(name)
         .ok_or(errors::AliasGroupNotTrackedError(format!(
             "Alias group {name} does not exist"
         )))?;
-    let old_path = PathBuf::from(alias.path);
+    let old_path = PathBuf::from(&alias.path);
     let updated_name = new_name.unwrap_or(name);
     let updated_path = new_path.as_ref().unwrap_or(&old_path);
     if old_path != *updated_path {
+        // relative links break when the group changes depth, so remember where they point before moving
+        let relative_links = get_alias_links(&old_path)?
+            .into_iter()
+            .filter(|link| fs::read_link(link).is_ok_and(|target| target.is_relative()))
+            .map(|link| Ok((link.file_name().unwrap().to_owned(), resolve_link(&link)?)))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
         fs::rename(&old_path, updated_path)?;
         log::info!(
             "Moved alias group from {} to {}",
             old_path.display(),
             updated_path.display()
         );
+        for (link_name, target) in relative_links {
+            let link = updated_path.join(link_name);
+            fs::remove_file(&link)?;
+            create_link(&target, &link, true)?;
+        }
     }
-    config.add_alias_group(
-        updated_name.to_string(),
-        &AliasGroup::new(updated_path.to_str().unwrap()),
-    );
+    alias.path = updated_path.to_str().unwrap().to_string();
+    config.add_alias_group(updated_name.to_string(), &alias);
     config.save(None, xdg)?;
     Ok(())
 }
@@ -520,6 +795,7 @@ pub fn update_alias_group(
 /// - `name` – The name of the alias group to untrack.
 /// - `xdg` – XDG configuration reference.
 pub fn untrack_alias_group(name: &str, xdg: &XDG) -> Result<(), errors::UntrackAliasGroupError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     config
         .delete_alias_group(name)
@@ -545,6 +821,15 @@ pub fn untrack_alias_group(name: &str, xdg: &XDG) -> Result<(), errors::UntrackA
         }
     }
 
+    let mut config = Config::load(None, xdg)?;
+    for (lib_name, mut lib_defaults) in config.get_all_library_defaults() {
+        if let Some(alias_groups) = lib_defaults.default_alias_groups.as_mut() {
+            alias_groups.retain(|x| x != name);
+        }
+        config.set_library_defaults(lib_name, lib_defaults);
+    }
+    config.save(None, xdg)?;
+
     for project_type in get_project_types(xdg)?.iter() {
         let mut new_alias_groups = project_type
             .1
@@ -555,7 +840,11 @@ pub fn untrack_alias_group(name: &str, xdg: &XDG) -> Result<(), errors::UntrackA
         define_project_type(
             project_type.0,
             Some(new_alias_groups),
-            project_type.1.builder.as_deref(),
+            project_type
+                .1
+                .builder
+                .as_ref()
+                .map(|builder| builder.steps().to_vec()),
             project_type.1.opener.as_deref(),
             true,
             xdg,
@@ -583,8 +872,448 @@ pub fn delete_alias_group(name: &str, xdg: &XDG) -> Result<(), errors::DeleteAli
     Ok(())
 }
 
+/// An alias symlink found in a tracked alias group.
+struct AliasLink {
+    link: PathBuf,
+    target: PathBuf,
+    relative: bool,
+}
+
+/// Find all aliases in tracked alias groups that point to a path inside `dir`.
+fn find_aliases_into(dir: &Path, config: &Config) -> Result<Vec<AliasLink>, std::io::Error> {
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let mut aliases = Vec::new();
+    for (_, alias) in config.get_alias_groups().unwrap_or_default() {
+        let group_path = Path::new(&alias.path);
+        if !group_path.is_dir() {
+            continue;
+        }
+        for link in get_alias_links(group_path)? {
+            let target = resolve_link(&link)?;
+            let target = fs::canonicalize(&target).unwrap_or(target);
+            if target.starts_with(&dir) {
+                aliases.push(AliasLink {
+                    relative: fs::read_link(&link)?.is_relative(),
+                    link,
+                    target,
+                });
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+/// To rename a library and move it to a new location. Every alias in a tracked alias group that points into the
+/// library is rewritten to point to the new location. Aliases outside of tracked alias groups can't be found and
+/// will break if the library is moved.
+///
+/// The `on_move` hooks of the projects run one after another before anything is moved. A hook that fails cancels
+/// the move, but what the hooks of the projects before it did is not undone.
+///
+/// # Arguments
+/// - `name` – The current name of the library.
+/// - `new_name` – Optional new name for the library.
+/// - `new_path` – Optional new path for the library, it must not exist yet.
+/// - `xdg` – XDG configuration reference.
+pub fn update_library(
+    name: &str,
+    new_name: Option<&str>,
+    new_path: Option<&str>,
+    xdg: &XDG,
+) -> Result<(), errors::UpdateLibraryError> {
+    let mut _lock = Config::lock(xdg)?;
+    let mut config = Config::load(None, xdg)?;
+    let old_path = PathBuf::from(config.get_lib_path(Some(name)).ok_or(
+        errors::LibNotTrackedError(format!("Library {name} does not exist")),
+    )?);
+    let updated_name = new_name.unwrap_or(name);
+    let updated_path = new_path.map(to_full_path).unwrap_or(old_path.clone());
+    if updated_name != name && config.get_lib_path(Some(updated_name)).is_some() {
+        Err(errors::LibAlreadyTrackedError(format!(
+            "Library {updated_name} already exists"
+        )))?;
+    }
+
+    if old_path != updated_path {
+        if updated_path.exists() {
+            Err(errors::LibPathExistsError(format!(
+                "Library path {} already exists",
+                updated_path.display()
+            )))?;
+        }
+        // hooks may call donna themselves, so they run without the lock and the config is reloaded after
+        drop(_lock);
+        for project in list_library_dirs(&old_path, &config)? {
+            let project_name = project.file_name().unwrap().to_str().unwrap();
+            let new_project_path = updated_path.join(project_name);
+            run_project_hook::<errors::UpdateLibraryError>(
+                ProjectHook::Move,
+                project_name,
+                &project,
+                Some(name),
+                new_project_path.to_str(),
+                &config,
+                xdg,
+            )?;
+        }
+        _lock = Config::lock(xdg)?;
+        config = Config::load(None, xdg)?;
+        // the hooks may have changed the config, so the library is looked up again
+        let old_path = PathBuf::from(config.get_lib_path(Some(name)).ok_or(
+            errors::LibNotTrackedError(format!("Library {name} does not exist")),
+        )?);
+        if updated_name != name && config.get_lib_path(Some(updated_name)).is_some() {
+            Err(errors::LibAlreadyTrackedError(format!(
+                "Library {updated_name} already exists"
+            )))?;
+        }
+        if updated_path.exists() {
+            Err(errors::LibPathExistsError(format!(
+                "Library path {} already exists",
+                updated_path.display()
+            )))?;
+        }
+        // find the aliases before moving, their targets can't be resolved afterwards
+        let old_canonical_path = fs::canonicalize(&old_path)?;
+        let aliases = find_aliases_into(&old_path, &config)?;
+        utils::move_dir(&old_path, &updated_path)?;
+        log::info!(
+            "Moved library from {} to {}",
+            old_path.display(),
+            updated_path.display()
+        );
+        for alias in aliases {
+            let new_target =
+                updated_path.join(alias.target.strip_prefix(&old_canonical_path).unwrap());
+            fs::remove_file(&alias.link)?;
+            create_link(&new_target, &alias.link, alias.relative)?;
+            log::info!("Rewrote alias {}", alias.link.display());
+        }
+    }
+
+    let lib_defaults = config.get_library_defaults(Some(name)).cloned();
+    config.delete_lib(name);
+    config.add_lib(
+        updated_name.to_string(),
+        updated_path.to_str().unwrap(),
+        false,
+    );
+    if let Some(lib_defaults) = lib_defaults {
+        config.set_library_defaults(updated_name.to_string(), lib_defaults);
+    }
+    if config.get_default_lib().as_deref() == Some(name) {
+        config.set_default_lib(updated_name.to_string());
+    }
+    if config.get_archive_lib().as_deref() == Some(name) {
+        config.set_archive_lib(Some(updated_name.to_string()));
+    }
+    config.save(None, xdg)?;
+    Ok(())
+}
+
+/// Get all aliases in tracked alias groups that point into a library.
+///
+/// # Arguments
+/// - `name` – The name of the library.
+/// - `xdg` – XDG configuration reference.
+pub fn get_library_aliases(
+    name: &str,
+    xdg: &XDG,
+) -> Result<Vec<PathBuf>, errors::GetLibraryAliasesError> {
+    let config = Config::load(None, xdg)?;
+    let lib_path = config
+        .get_lib_path(Some(name))
+        .ok_or(errors::LibNotTrackedError(format!(
+            "Library {name} does not exist"
+        )))?;
+    Ok(find_aliases_into(Path::new(lib_path), &config)?
+        .into_iter()
+        .map(|alias| alias.link)
+        .collect())
+}
+
+/// Delete a library and all projects in it. Aliases in tracked alias groups that point into the library are
+/// removed, the library directory is moved to the system trash and the library is untracked.
+///
+/// The `on_delete` hooks of the projects run one after another before anything is deleted. A hook that fails
+/// cancels the delete, but what the hooks of the projects before it did is not undone.
+///
+/// # Arguments
+/// - `name` – The name of the library to delete.
+/// - `xdg` – XDG configuration reference.
+pub fn delete_library(name: &str, xdg: &XDG) -> Result<(), errors::DeleteLibraryError> {
+    let mut _lock = Config::lock(xdg)?;
+    let mut config = Config::load(None, xdg)?;
+    let lib_path = PathBuf::from(config.get_lib_path(Some(name)).ok_or(
+        errors::LibNotTrackedError(format!("Library {name} does not exist")),
+    )?);
+    if lib_path.exists() {
+        // hooks may call donna themselves, so they run without the lock and the config is reloaded after
+        drop(_lock);
+        for project in list_library_dirs(&lib_path, &config)? {
+            let project_name = project.file_name().unwrap().to_str().unwrap();
+            run_project_hook::<errors::DeleteLibraryError>(
+                ProjectHook::Delete,
+                project_name,
+                &project,
+                Some(name),
+                None,
+                &config,
+                xdg,
+            )?;
+        }
+        _lock = Config::lock(xdg)?;
+        config = Config::load(None, xdg)?;
+        // the hooks may have changed the config, so the library is looked up again
+        let lib_path = PathBuf::from(config.get_lib_path(Some(name)).ok_or(
+            errors::LibNotTrackedError(format!("Library {name} does not exist")),
+        )?);
+        for alias in find_aliases_into(&lib_path, &config)? {
+            fs::remove_file(&alias.link)?;
+            log::info!("Removed alias {}", alias.link.display());
+        }
+        if lib_path.exists() {
+            delete(lib_path.to_str().unwrap())?;
+        }
+    }
+    config.delete_lib(name);
+    if config.get_default_lib().as_deref() == Some(name) {
+        config.clear_default_lib();
+    }
+    if config.get_archive_lib().as_deref() == Some(name) {
+        config.set_archive_lib(None);
+    }
+    config.save(None, xdg)?;
+    Ok(())
+}
+
+/// Delete a project. The `on_delete` hook of the project's type runs first and an error in it cancels the delete.
+/// Aliases in tracked alias groups that point into the project are removed and the project directory is moved to
+/// the system trash.
+///
+/// # Arguments
+/// - `name` – The name of the project.
+/// - `lib` – Optional library name to locate the project.
+/// - `xdg` – XDG configuration reference.
+pub fn delete_project(
+    name: &str,
+    lib: Option<api_types::LibraryName>,
+    xdg: &XDG,
+) -> Result<(), errors::DeleteProjectError> {
+    let get_project_path = |config: &Config| -> Result<PathBuf, errors::DeleteProjectError> {
+        let project_path = Path::new(config.get_lib_path(lib).ok_or(
+            errors::LibNotTrackedError(format!(
+                "Library {} does not exist",
+                lib.unwrap_or("[default]")
+            )),
+        )?)
+        .join(name);
+        if !project_path.exists() {
+            Err(errors::ProjectPathDoesNotExistError(format!(
+                "Project path {} does not exist",
+                project_path.display()
+            )))?;
+        }
+        Ok(project_path)
+    };
+    let config = Config::load(None, xdg)?;
+    let project_path = get_project_path(&config)?;
+    // hooks may call donna themselves, so they run without the lock
+    run_project_hook::<errors::DeleteProjectError>(
+        ProjectHook::Delete,
+        name,
+        &project_path,
+        lib,
+        None,
+        &config,
+        xdg,
+    )?;
+    let _lock = Config::lock(xdg)?;
+    let config = Config::load(None, xdg)?;
+    // the hook may have changed the config, so the project is looked up again
+    let project_path = get_project_path(&config)?;
+    for alias in find_aliases_into(&project_path, &config)? {
+        fs::remove_file(&alias.link)?;
+        log::info!("Removed alias {}", alias.link.display());
+    }
+    delete(project_path.to_str().unwrap())?;
+    Ok(())
+}
+
+/// Rename a project or move it to another library. Every alias in a tracked alias group that points into the
+/// project is rewritten to point to the new location, aliases named after the project are renamed with it.
+///
+/// The `on_rename` hook of the project's type runs if the name changes and the `on_move` hook if the library
+/// changes, both before anything is moved. A hook that fails cancels the update.
+///
+/// # Arguments
+/// - `name` – The name of the project.
+/// - `lib` – Optional library name to locate the project.
+/// - `new_name` – Optional new name for the project.
+/// - `new_lib` – Optional library to move the project to.
+/// - `xdg` – XDG configuration reference.
+pub fn update_project(
+    name: &str,
+    lib: Option<api_types::LibraryName>,
+    new_name: Option<&str>,
+    new_lib: Option<api_types::LibraryName>,
+    xdg: &XDG,
+) -> Result<(), errors::UpdateProjectError> {
+    let config = Config::load(None, xdg)?;
+    let mut hooks = Vec::new();
+    if new_name.is_some_and(|new_name| new_name != name) {
+        hooks.push(ProjectHook::Rename);
+    }
+    if new_lib.is_some_and(|new_lib| config.get_lib_name(lib) != new_lib) {
+        hooks.push(ProjectHook::Move);
+    }
+    move_project::<errors::UpdateProjectError>(
+        name,
+        lib,
+        new_name.unwrap_or(name),
+        new_lib.or(lib),
+        &hooks,
+        xdg,
+    )
+}
+
+/// Move a project to the archive library set with the `archive_lib` config key. Aliases pointing into the project
+/// are rewritten to point to its new location. The `on_archive` hook of the project's type runs before the project
+/// is moved, an error in it cancels the archiving.
+///
+/// # Arguments
+/// - `name` – The name of the project.
+/// - `lib` – Optional library name to locate the project.
+/// - `xdg` – XDG configuration reference.
+pub fn archive_project(
+    name: &str,
+    lib: Option<api_types::LibraryName>,
+    xdg: &XDG,
+) -> Result<(), errors::ArchiveProjectError> {
+    let config = Config::load(None, xdg)?;
+    let archive_lib = config.get_archive_lib().ok_or(errors::LibNotTrackedError(
+        "No archive library is set, set it with `donna config set archive_lib LIBRARY`".to_string(),
+    ))?;
+    if config.get_lib_name(lib) == archive_lib {
+        Err(errors::ProjectPathExistsError(format!(
+            "Project {name} is already in the archive library {archive_lib}"
+        )))?;
+    }
+    move_project::<errors::ArchiveProjectError>(
+        name,
+        lib,
+        name,
+        Some(&archive_lib),
+        &[ProjectHook::Archive],
+        xdg,
+    )
+}
+
+/// Get the current and new path of a project that is moved, the project has to exist and the new path must not.
+fn get_project_move_paths<E>(
+    name: &str,
+    lib: Option<&str>,
+    new_name: &str,
+    new_lib: Option<&str>,
+    config: &Config,
+) -> Result<(PathBuf, PathBuf), E>
+where
+    E: From<errors::LibNotTrackedError>
+        + From<errors::ProjectPathDoesNotExistError>
+        + From<errors::ProjectPathExistsError>,
+{
+    let lib_path = |lib: Option<&str>| {
+        config
+            .get_lib_path(lib)
+            .map(PathBuf::from)
+            .ok_or(errors::LibNotTrackedError(format!(
+                "Library {} does not exist",
+                lib.unwrap_or("[default]")
+            )))
+    };
+    let project_path = lib_path(lib)?.join(name);
+    let new_project_path = lib_path(new_lib)?.join(new_name);
+    if !project_path.exists() {
+        Err(errors::ProjectPathDoesNotExistError(format!(
+            "Project path {} does not exist",
+            project_path.display()
+        )))?;
+    }
+    if project_path != new_project_path && new_project_path.exists() {
+        Err(errors::ProjectPathExistsError(format!(
+            "Project path {} already exists",
+            new_project_path.display()
+        )))?;
+    }
+    Ok((project_path, new_project_path))
+}
+
+/// Run the hooks of a project and move it to a new name or library, rewriting the aliases that point into it.
+fn move_project<E>(
+    name: &str,
+    lib: Option<&str>,
+    new_name: &str,
+    new_lib: Option<&str>,
+    hooks: &[ProjectHook],
+    xdg: &XDG,
+) -> Result<(), E>
+where
+    E: From<errors::ConfigError>
+        + From<errors::LibNotTrackedError>
+        + From<errors::ProjectPathDoesNotExistError>
+        + From<errors::ProjectPathExistsError>
+        + From<std::io::Error>
+        + From<errors::HookRuntimeError>
+        + From<errors::ExecutableError>
+        + From<errors::ProjectConfigError>,
+{
+    let config = Config::load(None, xdg)?;
+    let (project_path, new_project_path) =
+        get_project_move_paths::<E>(name, lib, new_name, new_lib, &config)?;
+    if project_path == new_project_path {
+        return Ok(());
+    }
+    // hooks may call donna themselves, so they run without the lock
+    for hook in hooks {
+        let target = match hook {
+            ProjectHook::Rename => new_name,
+            _ => new_project_path.to_str().unwrap(),
+        };
+        run_project_hook::<E>(*hook, name, &project_path, lib, Some(target), &config, xdg)?;
+    }
+    let _lock = Config::lock(xdg)?;
+    let config = Config::load(None, xdg)?;
+    // the hooks may have changed the config, so the paths are looked up again
+    let (project_path, new_project_path) =
+        get_project_move_paths::<E>(name, lib, new_name, new_lib, &config)?;
+    // find the aliases before moving, their targets can't be resolved afterwards
+    let old_canonical_path = fs::canonicalize(&project_path)?;
+    let aliases = find_aliases_into(&project_path, &config)?;
+    utils::move_dir(&project_path, &new_project_path)?;
+    log::info!(
+        "Moved project from {} to {}",
+        project_path.display(),
+        new_project_path.display()
+    );
+    for alias in aliases {
+        let new_target = match alias.target.strip_prefix(&old_canonical_path).unwrap() {
+            rest if rest.as_os_str().is_empty() => new_project_path.clone(),
+            rest => new_project_path.join(rest),
+        };
+        let new_link = match alias.link.file_name() == Some(name.as_ref()) {
+            true => alias.link.with_file_name(new_name),
+            false => alias.link.clone(),
+        };
+        fs::remove_file(&alias.link)?;
+        create_link(&new_target, &new_link, alias.relative)?;
+        log::info!("Rewrote alias {}", new_link.display());
+    }
+    Ok(())
+}
+
 /// Untrack a library
 pub fn untrack_library(name: &str, xdg: &XDG) -> Result<(), errors::UntrackLibError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     config
         .delete_lib(name)
@@ -597,12 +1326,19 @@ pub fn untrack_library(name: &str, xdg: &XDG) -> Result<(), errors::UntrackLibEr
 
 /// Untrack a project type
 pub fn untrack_project_type(name: &str, xdg: &XDG) -> Result<(), errors::UntrackProjectTypeError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     config
         .delete_project_type(name)
         .ok_or(errors::ProjectTypeNotTrackedError(format!(
             "Project type {name} does not exist"
         )))?;
+    for (lib_name, mut lib_defaults) in config.get_all_library_defaults() {
+        if lib_defaults.project_type.as_deref() == Some(name) {
+            lib_defaults.project_type = None;
+            config.set_library_defaults(lib_name, lib_defaults);
+        }
+    }
     config.save(None, xdg)?;
 
     for project in get_projects(xdg)?.iter() {
@@ -618,7 +1354,83 @@ pub fn untrack_project_type(name: &str, xdg: &XDG) -> Result<(), errors::Untrack
     Ok(())
 }
 
-/// Get all projects that are tracked by donna, in all libraries.
+/// Get the directories in a library that are not ignored. Ignore patterns come from the built in defaults, the
+/// config variable `ignore_patterns` and the `.donnaignore` file in the library root, they are matched against
+/// the directory name.
+fn list_library_dirs(lib_path: &Path, config: &Config) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut patterns = config.get_ignore_patterns();
+    let ignore_file = lib_path.join(Config::LIBRARY_IGNORE_FILE_NAME);
+    if ignore_file.is_file() {
+        patterns.extend(
+            fs::read_to_string(&ignore_file)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+    let patterns: Vec<glob::Pattern> = patterns
+        .iter()
+        .filter_map(|pattern| {
+            glob::Pattern::new(pattern)
+                .inspect_err(|e| log::warn!("Invalid ignore pattern '{pattern}': {e}"))
+                .ok()
+        })
+        .collect();
+
+    let mut dirs = Vec::new();
+    for entry in lib_path.read_dir()? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if !entry.file_type()?.is_dir() || patterns.iter().any(|p| p.matches(&file_name)) {
+            continue;
+        }
+        dirs.push(entry.path());
+    }
+    Ok(dirs)
+}
+
+/// Get the project directories in a library, skipping ignored directories. The directories don't need to contain
+/// a project config, this is used to find projects to import.
+///
+/// # Arguments
+/// - `name` – The name of the library.
+/// - `xdg` – XDG configuration reference.
+pub fn get_library_dirs(
+    name: &str,
+    xdg: &XDG,
+) -> Result<Vec<PathBuf>, errors::GetLibraryDirsError> {
+    let config = Config::load(None, xdg)?;
+    let lib_path = config
+        .get_lib_path(Some(name))
+        .ok_or(errors::LibNotTrackedError(format!(
+            "Library {name} does not exist"
+        )))?;
+    Ok(list_library_dirs(Path::new(lib_path), &config)?)
+}
+
+/// Set the global patterns of directories to skip when scanning libraries.
+///
+/// # Arguments
+/// - `patterns` – Glob patterns matched against directory names, None removes them.
+/// - `xdg` – XDG configuration reference.
+pub fn set_ignore_patterns(
+    patterns: Option<Vec<String>>,
+    xdg: &XDG,
+) -> Result<(), errors::SetIgnorePatternsError> {
+    for pattern in patterns.iter().flatten() {
+        glob::Pattern::new(pattern)?;
+    }
+    let _lock = Config::lock(xdg)?;
+    let mut config = Config::load(None, xdg)?;
+    config.set_ignore_patterns(patterns);
+    config.save(None, xdg)?;
+    Ok(())
+}
+
+/// Get all projects that are tracked by donna, in all libraries. Libraries that are unavailable, for example on
+/// an external drive that isn't mounted, are skipped with a warning.
 ///
 /// # Arguments
 /// - `xdg` – XDG configuration reference.
@@ -636,17 +1448,13 @@ pub fn get_projects(
         .ok_or(errors::LibNotTrackedError("No libraries found".to_string()))?
         .iter()
     {
-        let projects = Path::new(lib_path).read_dir()?.filter_map(|f| {
-            f.as_ref()
-                .unwrap()
-                .file_type()
-                .unwrap()
-                .is_dir()
-                .then(|| f.unwrap())
-        });
-        for project in projects {
-            let project_name = project.file_name().to_string_lossy().to_string();
-            let project_config_path = project.path().join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+        if !Path::new(lib_path).is_dir() {
+            log::warn!("Library {lib_name} is unavailable, {lib_path} does not exist");
+            continue;
+        }
+        for project in list_library_dirs(Path::new(lib_path), &config)? {
+            let project_name = project.file_name().unwrap().to_string_lossy().to_string();
+            let project_config_path = project.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
             let project_config = match ProjectConfig::load(project_config_path.to_str().unwrap()) {
                 Ok(config) => config,
                 Err(e) => {
@@ -663,7 +1471,7 @@ pub fn get_projects(
                         .clone()
                         .unwrap_or("".to_string()),
                     lib_name.clone(),
-                    project.path().to_str().unwrap().to_string(),
+                    project.to_str().unwrap().to_string(),
                 ),
             );
         }
@@ -682,6 +1490,22 @@ pub fn get_libraries(xdg: &XDG) -> Result<HashMap<String, String>, errors::GetLi
         .ok_or(errors::LibNotTrackedError("No libraries found".to_string()))?)
 }
 
+/// Get the names of tracked libraries whose directory does not exist, for example because they are on an external
+/// drive that isn't mounted.
+///
+/// # Arguments
+/// - `xdg` – XDG configuration reference.
+pub fn get_offline_libraries(xdg: &XDG) -> Result<Vec<String>, errors::GetLibsError> {
+    let config = Config::load(None, xdg)?;
+    Ok(config
+        .get_libs()
+        .ok_or(errors::LibNotTrackedError("No libraries found".to_string()))?
+        .into_iter()
+        .filter(|(_, path)| !Path::new(path).is_dir())
+        .map(|(name, _)| name)
+        .collect())
+}
+
 /// Get all alias groups that are tracked by donna
 ///
 /// # Arguments
@@ -717,6 +1541,7 @@ pub fn set_builders_path_prefix(
             "Path {path} does not exist"
         )))?;
     }
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     config.set_builders_path_prefix(to_full_path(path).to_str().unwrap());
     config.save(None, xdg)?;
@@ -732,6 +1557,7 @@ pub fn set_openers_path_prefix(
             "Path {path} does not exist"
         )))?;
     }
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     config.set_openers_path_prefix(to_full_path(path).to_str().unwrap());
     config.save(None, xdg)?;
@@ -739,6 +1565,7 @@ pub fn set_openers_path_prefix(
 }
 
 pub fn set_default_lib(name: &str, xdg: &XDG) -> Result<(), errors::SetDefaultLibError> {
+    let _lock = Config::lock(xdg)?;
     let mut config = Config::load(None, xdg)?;
     if config.get_lib_path(Some(name)).is_none() {
         Err(errors::LibNotTrackedError(format!(
@@ -750,6 +1577,1308 @@ pub fn set_default_lib(name: &str, xdg: &XDG) -> Result<(), errors::SetDefaultLi
     Ok(())
 }
 
+/// Set the defaults that are applied to projects created in or imported into a library. The project type is used
+/// when no type is given, the alias groups are added to the ones from the project type, and the builder and opener
+/// override the ones from the project type.
+///
+/// # Arguments
+/// - `name` – The name of the library.
+/// - `project_type` – Optional default project type.
+/// - `default_alias_groups` – Optional alias groups every project in the library is added to.
+/// - `builder` – Optional builder override, scripts run in order, relative to the config variable `builders_dir`.
+/// - `opener` – Optional opener override, relative to the config variable `openers_dir`.
+/// - `xdg` – XDG configuration reference.
+pub fn set_library_defaults(
+    name: &str,
+    project_type: Option<&str>,
+    default_alias_groups: Option<Vec<String>>,
+    builder: Option<Vec<String>>,
+    opener: Option<&str>,
+    xdg: &XDG,
+) -> Result<(), errors::SetLibraryDefaultsError> {
+    let _lock = Config::lock(xdg)?;
+    let mut config = Config::load(None, xdg)?;
+    if config.get_lib_path(Some(name)).is_none() {
+        Err(errors::LibNotTrackedError(format!(
+            "Library {name} does not exist"
+        )))?;
+    }
+    let lib_defaults = config.new_library_defaults(
+        project_type.map(|s| s.to_string()),
+        default_alias_groups,
+        builder,
+        opener,
+    );
+    check_library_defaults::<errors::SetLibraryDefaultsError>(&config, &lib_defaults)?;
+    config.set_library_defaults(name.to_string(), lib_defaults);
+    config.save(None, xdg)?;
+    Ok(())
+}
+
+/// Check that the project type, alias groups and scripts of library defaults exist.
+fn check_library_defaults<E>(config: &Config, defaults: &LibraryDefaults) -> Result<(), E>
+where
+    E: From<errors::ProjectTypeNotTrackedError>
+        + From<errors::AliasGroupNotTrackedError>
+        + From<errors::BuilderPathNotFoundError>
+        + From<errors::OpenerPathNotFoundError>,
+{
+    if let Some(pt) = &defaults.project_type {
+        if config.get_project_type(pt.to_string()).is_none() {
+            Err(errors::ProjectTypeNotTrackedError(format!(
+                "Project type {pt} does not exist"
+            )))?;
+        }
+    }
+    for alias_group in defaults.default_alias_groups.iter().flatten() {
+        if config.get_alias_group(alias_group).is_none() {
+            Err(errors::AliasGroupNotTrackedError(format!(
+                "Alias group {alias_group} does not exist"
+            )))?;
+        }
+    }
+    for step in defaults.builder.iter().flat_map(|builder| builder.steps()) {
+        if !Path::new(step).is_file() {
+            Err(errors::BuilderPathNotFoundError(format!(
+                "Builder path {step} does not exist"
+            )))?;
+        }
+    }
+    if let Some(opener) = defaults.opener.as_ref().filter(|o| !Path::new(o).is_file()) {
+        Err(errors::OpenerPathNotFoundError(format!(
+            "Opener path {opener} does not exist"
+        )))?;
+    }
+    Ok(())
+}
+
+/// Get the defaults of all libraries that have any.
+///
+/// # Arguments
+/// - `xdg` – XDG configuration reference.
+pub fn get_library_defaults(
+    xdg: &XDG,
+) -> Result<HashMap<String, LibraryDefaults>, errors::ConfigError> {
+    let config = Config::load(None, xdg)?;
+    Ok(config.get_all_library_defaults())
+}
+
+/// Set whether aliases should be created as relative symlinks, for a single alias group or for the whole config if
+/// `alias_group` is None. Passing None for `relative` clears the option so the group falls back to the config setting.
+/// This only affects new aliases, use `repair_alias_links` to convert existing ones.
+///
+/// # Arguments
+/// - `relative` – Whether to use relative symlinks.
+/// - `alias_group` – Optional alias group to set the option for.
+/// - `xdg` – XDG configuration reference.
+pub fn set_relative_links(
+    relative: Option<bool>,
+    alias_group: Option<&str>,
+    xdg: &XDG,
+) -> Result<(), errors::SetRelativeLinksError> {
+    let _lock = Config::lock(xdg)?;
+    let mut config = Config::load(None, xdg)?;
+    match alias_group {
+        Some(name) => {
+            let mut alias = config
+                .get_alias_group(name)
+                .ok_or(errors::AliasGroupNotTrackedError(format!(
+                    "Alias group {name} does not exist"
+                )))?
+                .clone();
+            alias.relative_links = relative;
+            config.add_alias_group(name.to_string(), &alias);
+        }
+        None => config.set_relative_links(relative),
+    }
+    config.save(None, xdg)?;
+    Ok(())
+}
+
+/// A problem found by `doctor`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthIssue {
+    /// The directory of a tracked alias group does not exist
+    AliasGroupPathMissing { alias_group: String, path: PathBuf },
+    /// An alias points to a path that does not exist
+    BrokenLink {
+        alias_group: String,
+        link: PathBuf,
+        target: PathBuf,
+    },
+    /// An alias is absolute while the config says it should be relative, or the other way around
+    LinkStyleMismatch {
+        alias_group: String,
+        link: PathBuf,
+        expected_relative: bool,
+    },
+    /// The directory of a tracked library does not exist, aliases into it are not reported as broken
+    LibraryOffline { lib: String, path: PathBuf },
+    /// A directory in a library that has no project config and is not ignored
+    UntrackedProjectDir { lib: String, path: PathBuf },
+}
+
+impl fmt::Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthIssue::AliasGroupPathMissing { alias_group, path } => write!(
+                f,
+                "Alias group '{alias_group}' path {} does not exist",
+                path.display()
+            ),
+            HealthIssue::BrokenLink {
+                alias_group,
+                link,
+                target,
+            } => write!(
+                f,
+                "Broken alias in '{alias_group}': {} -> {}",
+                link.display(),
+                target.display()
+            ),
+            HealthIssue::LinkStyleMismatch {
+                alias_group,
+                link,
+                expected_relative,
+            } => write!(
+                f,
+                "Alias in '{alias_group}' should be {}: {}",
+                if *expected_relative {
+                    "relative"
+                } else {
+                    "absolute"
+                },
+                link.display()
+            ),
+            HealthIssue::LibraryOffline { lib, path } => write!(
+                f,
+                "Library '{lib}' is unavailable, {} does not exist",
+                path.display()
+            ),
+            HealthIssue::UntrackedProjectDir { lib, path } => write!(
+                f,
+                "Directory in library '{lib}' is not a project: {}",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Get all symlinks directly inside a directory.
+fn get_alias_links(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut links = Vec::new();
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        if entry.file_type()?.is_symlink() {
+            links.push(entry.path());
+        }
+    }
+    Ok(links)
+}
+
+/// Check all tracked alias groups for missing directories, broken aliases and aliases that don't match the
+/// configured link style, and all libraries for directories that are not projects.
+///
+/// # Arguments
+/// - `xdg` – XDG configuration reference.
+pub fn doctor(xdg: &XDG) -> Result<Vec<HealthIssue>, errors::DoctorError> {
+    let config = Config::load(None, xdg)?;
+    let mut issues = Vec::new();
+    let offline_lib_paths: Vec<PathBuf> = config
+        .get_libs()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, path)| !Path::new(path).is_dir())
+        .map(|(lib, path)| {
+            issues.push(HealthIssue::LibraryOffline {
+                lib,
+                path: PathBuf::from(&path),
+            });
+            PathBuf::from(path)
+        })
+        .collect();
+    for (name, alias) in config.get_alias_groups().unwrap_or_default() {
+        let group_path = PathBuf::from(&alias.path);
+        if !group_path.is_dir() {
+            issues.push(HealthIssue::AliasGroupPathMissing {
+                alias_group: name,
+                path: group_path,
+            });
+            continue;
+        }
+        let expected_relative = config.uses_relative_links(&name);
+        for link in get_alias_links(&group_path)? {
+            let target = resolve_link(&link)?;
+            if !target.exists() && offline_lib_paths.iter().any(|p| target.starts_with(p)) {
+                continue;
+            } else if !target.exists() {
+                issues.push(HealthIssue::BrokenLink {
+                    alias_group: name.clone(),
+                    link,
+                    target,
+                });
+            } else if fs::read_link(&link)?.is_relative() != expected_relative {
+                issues.push(HealthIssue::LinkStyleMismatch {
+                    alias_group: name.clone(),
+                    link,
+                    expected_relative,
+                });
+            }
+        }
+    }
+    for (lib, lib_path) in config.get_libs().unwrap_or_default() {
+        let lib_path = Path::new(&lib_path);
+        if !lib_path.is_dir() {
+            continue;
+        }
+        for path in list_library_dirs(lib_path, &config)? {
+            if !path.join(ProjectConfig::PROJECT_ROOT_REL_PATH).exists() {
+                issues.push(HealthIssue::UntrackedProjectDir {
+                    lib: lib.clone(),
+                    path,
+                });
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Rewrite existing aliases as relative or absolute symlinks. Broken aliases are left untouched.
+///
+/// # Arguments
+/// - `alias_group` – Optional alias group to repair, all tracked groups are repaired if None.
+/// - `relative` – Link style to convert to, if None the configured style of each group is used.
+/// - `xdg` – XDG configuration reference.
+/// # Returns
+/// - The number of aliases that were rewritten.
+pub fn repair_alias_links(
+    alias_group: Option<&str>,
+    relative: Option<bool>,
+    xdg: &XDG,
+) -> Result<usize, errors::RepairAliasLinksError> {
+    let config = Config::load(None, xdg)?;
+    let alias_groups = match alias_group {
+        Some(name) => {
+            let alias = config
+                .get_alias_group(name)
+                .ok_or(errors::AliasGroupNotTrackedError(format!(
+                    "Alias group {name} does not exist"
+                )))?;
+            HashMap::from([(name.to_string(), alias.clone())])
+        }
+        None => config.get_alias_groups().unwrap_or_default(),
+    };
+
+    let mut rewritten = 0;
+    for (name, alias) in alias_groups {
+        let group_path = PathBuf::from(&alias.path);
+        if !group_path.is_dir() {
+            log::warn!(
+                "Skipping alias group {name}, {} does not exist",
+                group_path.display()
+            );
+            continue;
+        }
+        let make_relative = relative.unwrap_or_else(|| config.uses_relative_links(&name));
+        for link in get_alias_links(&group_path)? {
+            let target = resolve_link(&link)?;
+            if !target.exists() || fs::read_link(&link)?.is_relative() == make_relative {
+                continue;
+            }
+            fs::remove_file(&link)?;
+            create_link(&target, &link, make_relative)?;
+            log::info!("Rewrote alias {}", link.display());
+            rewritten += 1;
+        }
+    }
+    Ok(rewritten)
+}
+
+/// Upgrade the config and the project configs in every available library to the current version. Files are
+/// backed up before they are rewritten, see the migrations module.
+///
+/// # Arguments
+/// - `check` – Only report what would change, without writing anything.
+/// - `xdg` – XDG configuration reference.
+/// # Returns
+/// - The path of each file that needs migrating and the changes made to it.
+pub fn migrate(
+    check: bool,
+    xdg: &XDG,
+) -> Result<Vec<(PathBuf, Vec<String>)>, errors::MigrateError> {
+    let _lock = Config::lock(xdg)?;
+    let mut migrated = Vec::new();
+
+    let config_path = Config::get_path(xdg);
+    let (config, changes) = Config::load_unmigrated(&config_path, xdg)?;
+    if !changes.is_empty() {
+        if !check {
+            Config::migrate_file(&config_path)?;
+        }
+        migrated.push((config_path, changes));
+    }
+
+    for (lib_name, lib_path) in config.get_libs().unwrap_or_default() {
+        if !Path::new(&lib_path).is_dir() {
+            log::warn!("Library {lib_name} is unavailable, {lib_path} does not exist");
+            continue;
+        }
+        for project in list_library_dirs(Path::new(&lib_path), &config)? {
+            let project_config_path = project.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+            if !project_config_path.is_file() {
+                continue;
+            }
+            let path = project_config_path.to_str().unwrap();
+            let changes = ProjectConfig::check_migration(path)?;
+            if changes.is_empty() {
+                continue;
+            }
+            if !check {
+                ProjectConfig::migrate_file(path)?;
+            }
+            migrated.push((project_config_path, changes));
+        }
+    }
+    Ok(migrated)
+}
+
+/// What is wrong with a key found by `validate_config`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigIssueKind {
+    /// The key is not used by donna, usually a typo
+    UnknownKey,
+    /// The default library is not a tracked library
+    DefaultLibNotTracked { lib: String },
+    /// The archive library is not a tracked library
+    ArchiveLibNotTracked { lib: String },
+    /// An alias group that is not tracked is referenced
+    UnknownAliasGroup { alias_group: String },
+    /// A project type that is not tracked is referenced
+    UnknownProjectType { project_type: String },
+    /// A builder, opener or hook script does not exist
+    ScriptNotFound { path: PathBuf },
+}
+
+/// A problem found by `validate_config`, with the key it was found at and where that key is in the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// Path of the key from the root of the config, e.g. `["project_types", "rust", "builder"]`
+    pub key: Vec<String>,
+    /// Config file the key is set in, the user's config or a shared file layered under it
+    pub file: PathBuf,
+    /// Line and column of the key in the config file, both starting at 1
+    pub location: Option<(usize, usize)>,
+    pub kind: ConfigIssueKind,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = format_config_key(&self.key);
+        match &self.kind {
+            ConfigIssueKind::UnknownKey => write!(f, "Unknown key '{key}'"),
+            ConfigIssueKind::DefaultLibNotTracked { lib }
+            | ConfigIssueKind::ArchiveLibNotTracked { lib } => {
+                write!(f, "'{key}' is library '{lib}' which is not tracked")
+            }
+            ConfigIssueKind::UnknownAliasGroup { alias_group } => {
+                write!(
+                    f,
+                    "'{key}' references alias group '{alias_group}' which is not tracked"
+                )
+            }
+            ConfigIssueKind::UnknownProjectType { project_type } => write!(
+                f,
+                "'{key}' references project type '{project_type}' which is not tracked"
+            ),
+            ConfigIssueKind::ScriptNotFound { path } => {
+                write!(f, "'{key}' script {} does not exist", path.display())
+            }
+        }
+    }
+}
+
+/// Find values in a config that reference libraries, alias groups, project types or scripts that don't exist.
+fn find_broken_references(config: &Config) -> Vec<(Vec<String>, ConfigIssueKind)> {
+    fn builder_steps(builder: &Option<Builder>) -> Vec<&String> {
+        builder.iter().flat_map(|builder| builder.steps()).collect()
+    }
+    let mut found: Vec<(Vec<&str>, ConfigIssueKind)> = Vec::new();
+    let libs = config.get_libs().unwrap_or_default();
+    let alias_groups = config.get_alias_groups().unwrap_or_default();
+    let project_types = config.get_project_types().unwrap_or_default();
+    if let Some(lib) = config.get_default_lib() {
+        if !libs.contains_key(&lib) {
+            found.push((
+                vec!["default_lib"],
+                ConfigIssueKind::DefaultLibNotTracked { lib },
+            ));
+        }
+    }
+    if let Some(lib) = config.get_archive_lib() {
+        if !libs.contains_key(&lib) {
+            found.push((
+                vec!["archive_lib"],
+                ConfigIssueKind::ArchiveLibNotTracked { lib },
+            ));
+        }
+    }
+
+    let library_defaults = config.get_all_library_defaults();
+    let entries = project_types
+        .iter()
+        .map(|(name, t)| {
+            let scripts = vec![
+                ("builder", builder_steps(&t.builder)),
+                ("opener", t.opener.iter().collect()),
+                ("on_delete", t.on_delete.iter().collect()),
+                ("on_move", t.on_move.iter().collect()),
+                ("on_rename", t.on_rename.iter().collect()),
+                ("on_archive", t.on_archive.iter().collect()),
+                ("on_close", t.on_close.iter().collect()),
+                ("on_add_to_group", t.on_add_to_group.iter().collect()),
+            ];
+            (
+                "project_types",
+                name,
+                &t.default_alias_groups,
+                scripts,
+                None,
+            )
+        })
+        .chain(library_defaults.iter().map(|(name, d)| {
+            let scripts = vec![
+                ("builder", builder_steps(&d.builder)),
+                ("opener", d.opener.iter().collect()),
+            ];
+            let project_type = d.project_type.as_ref();
+            (
+                "library_defaults",
+                name,
+                &d.default_alias_groups,
+                scripts,
+                project_type,
+            )
+        }));
+    for (section, name, groups, scripts, project_type) in entries {
+        for alias_group in groups.iter().flatten() {
+            if !alias_groups.contains_key(alias_group) {
+                found.push((
+                    vec![section, name, "default_alias_groups"],
+                    ConfigIssueKind::UnknownAliasGroup {
+                        alias_group: alias_group.clone(),
+                    },
+                ));
+            }
+        }
+        for (key, scripts) in scripts {
+            for script in scripts.into_iter().filter(|s| !Path::new(s).is_file()) {
+                found.push((
+                    vec![section, name, key],
+                    ConfigIssueKind::ScriptNotFound {
+                        path: PathBuf::from(script),
+                    },
+                ));
+            }
+        }
+        if let Some(project_type) = project_type.filter(|t| !project_types.contains_key(*t)) {
+            found.push((
+                vec![section, name, "project_type"],
+                ConfigIssueKind::UnknownProjectType {
+                    project_type: project_type.clone(),
+                },
+            ));
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|(key, kind)| (key.into_iter().map(str::to_string).collect(), kind))
+        .collect()
+}
+
+/// Check the config file for unknown keys and references to things that don't exist. Syntax and type errors are
+/// returned as a `ConfigError::TomlLoad` with the line and column of the error.
+///
+/// # Arguments
+/// - `xdg` – XDG configuration reference.
+/// # Returns
+/// - The issues found, in the order they appear in the config files.
+pub fn validate_config(xdg: &XDG) -> Result<Vec<ConfigIssue>, errors::ValidateConfigError> {
+    let path = Config::get_path(xdg);
+    let contents = fs::read_to_string(&path).map_err(errors::ConfigError::from)?;
+    let table: toml::Table = toml::from_str(&contents)
+        .map_err(|e| errors::ConfigError::TomlLoad(path.clone(), Box::new(e)))?;
+    let (config, _) = Config::load_unmigrated(&path, xdg)?;
+
+    let mut found: Vec<(Vec<String>, ConfigIssueKind)> = Config::find_unknown_keys(&table)
+        .into_iter()
+        .map(|key| (key, ConfigIssueKind::UnknownKey))
+        .collect();
+
+    found.extend(find_broken_references(&config));
+
+    let mut issues: Vec<ConfigIssue> = found
+        .into_iter()
+        .map(|(key, kind)| {
+            let file = config
+                .get_origin(&key)
+                .map_or_else(|| path.clone(), Path::to_path_buf);
+            let location = if file == path {
+                Config::locate_key(&contents, &key)
+            } else {
+                Config::locate_key(&fs::read_to_string(&file).unwrap_or_default(), &key)
+            };
+            ConfigIssue {
+                key,
+                file,
+                location,
+                kind,
+            }
+        })
+        .collect();
+    // issues in the user's config first, then the shared files they are layered on
+    issues.sort_by_key(|issue| {
+        (
+            issue.file != path,
+            issue.file.clone(),
+            issue.location.unwrap_or((usize::MAX, 0)),
+        )
+    });
+    Ok(issues)
+}
+
+/// A value in the merged config and the file it came from, see `get_config_values`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigValue {
+    /// Path of the key from the root of the config, e.g. `["project_types", "rust", "builder"]`
+    pub key: Vec<String>,
+    pub value: toml::Value,
+    /// Config file the value was set in, None for default values
+    pub origin: Option<PathBuf>,
+}
+
+fn flatten_config_table(
+    table: &toml::Table,
+    prefix: &[String],
+    config: &Config,
+    values: &mut Vec<ConfigValue>,
+) {
+    for (key, value) in table {
+        let mut path = prefix.to_vec();
+        path.push(key.clone());
+        match value {
+            toml::Value::Table(table) => flatten_config_table(table, &path, config, values),
+            _ => values.push(ConfigValue {
+                origin: config.get_origin(&path).map(Path::to_path_buf),
+                key: path,
+                value: value.clone(),
+            }),
+        }
+    }
+}
+
+/// Get every value of the config after the shared config files are merged, with the file each one was set in.
+///
+/// # Arguments
+/// - `xdg` – XDG configuration reference.
+/// # Returns
+/// - The values sorted by key.
+pub fn get_config_values(xdg: &XDG) -> Result<Vec<ConfigValue>, errors::ConfigError> {
+    let config = Config::load(None, xdg)?;
+    let mut values = Vec::new();
+    flatten_config_table(&toml::Table::try_from(&config)?, &[], &config, &mut values);
+    values.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(values)
+}
+
+/// Split a dotted key into its parts, parts with dots are quoted like in TOML, e.g. `library_paths."my.lib"`
+fn parse_config_key(key: &str) -> Result<Vec<String>, errors::InvalidConfigKeyError> {
+    let parts = toml_edit::Key::parse(key).map_err(|_| {
+        errors::InvalidConfigKeyError(format!(
+            "{key} is not a dotted key, quote parts with dots or spaces like library_paths.\"my.lib\""
+        ))
+    })?;
+    Ok(parts.iter().map(|part| part.get().to_string()).collect())
+}
+
+/// Join the parts of a key with dots, quoting the parts that need it, the reverse of `parse_config_key`
+pub fn format_config_key(key: &[String]) -> String {
+    key.iter()
+        .map(|part| {
+            toml_edit::Key::new(part.as_str())
+                .display_repr()
+                .into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Get the value of a config key, e.g. `project_types.rust.builder`, or of a whole table, e.g. `project_types`.
+///
+/// # Arguments
+/// - `key` – Dotted path of the key.
+/// - `xdg` – XDG configuration reference.
+/// # Returns
+/// - The value, or None if the key is not set.
+pub fn get_config_value(
+    key: &str,
+    xdg: &XDG,
+) -> Result<Option<toml::Value>, errors::ConfigValueError> {
+    let key = parse_config_key(key)?;
+    let config = Config::load(None, xdg)?;
+    let value = config.get_value(&key)?;
+    if value.is_none() && Config::get_key_kind(&key).is_none() {
+        Err(errors::InvalidConfigKeyError(format!(
+            "{} is not a config key",
+            format_config_key(&key)
+        )))?;
+    }
+    Ok(value)
+}
+
+/// Check that a change to the config doesn't make it reference things that don't exist.
+fn check_config_change(
+    key: &[String],
+    old_config: &Config,
+    config: &Config,
+    xdg: &XDG,
+) -> Result<(), errors::InvalidConfigValueError> {
+    let old_issues = find_broken_references(old_config);
+    match find_broken_references(config)
+        .into_iter()
+        .find(|issue| !old_issues.contains(issue))
+    {
+        Some((issue_key, kind)) => Err(errors::InvalidConfigValueError(format!(
+            "Can't change {}, {}",
+            format_config_key(key),
+            ConfigIssue {
+                key: issue_key,
+                file: Config::get_path(xdg),
+                location: None,
+                kind,
+            }
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Set a config key, e.g. `project_types.rust.builder`. The values are converted to the type of the key, lists
+/// take any number of values and everything else exactly one. Relative paths are relative to the current directory,
+/// builders and openers to the builders and openers directories. Paths written with `~` or variables are saved as
+/// written.
+///
+/// # Arguments
+/// - `key` – Dotted path of the key.
+/// - `values` – The value, or the items for a list.
+/// - `xdg` – XDG configuration reference.
+pub fn set_config_value(
+    key: &str,
+    values: &[&str],
+    xdg: &XDG,
+) -> Result<(), errors::ConfigValueError> {
+    let key = parse_config_key(key)?;
+    let kind = Config::get_key_kind(&key).ok_or(errors::InvalidConfigKeyError(format!(
+        "{} is not a config key that can be set",
+        format_config_key(&key)
+    )))?;
+    let single = || match values {
+        [value] => Ok(*value),
+        _ => Err(errors::InvalidConfigValueError(format!(
+            "{} takes exactly one value",
+            format_config_key(&key)
+        ))),
+    };
+
+    let _lock = Config::lock(xdg)?;
+    let mut config = Config::load(None, xdg)?;
+    let value = match kind {
+        ConfigValueKind::String => toml::Value::String(single()?.to_string()),
+        ConfigValueKind::Bool => toml::Value::Boolean(single()?.parse().map_err(|_| {
+            errors::InvalidConfigValueError(format!(
+                "{} must be true or false",
+                format_config_key(&key)
+            ))
+        })?),
+        ConfigValueKind::StringList => {
+            if key == ["ignore_patterns"] {
+                for pattern in values {
+                    glob::Pattern::new(pattern).map_err(|e| {
+                        errors::InvalidConfigValueError(format!("{pattern} is not a pattern: {e}"))
+                    })?;
+                }
+            }
+            toml::Value::Array(
+                values
+                    .iter()
+                    .map(|value| toml::Value::String(value.to_string()))
+                    .collect(),
+            )
+        }
+        ConfigValueKind::Path
+        | ConfigValueKind::Builder
+        | ConfigValueKind::BuilderSteps
+        | ConfigValueKind::Opener => {
+            // a builder can be a list of steps, other paths take one value
+            let raws = match kind {
+                ConfigValueKind::BuilderSteps if !values.is_empty() => values.to_vec(),
+                _ => vec![single()?],
+            };
+            let mut paths = Vec::new();
+            for (i, raw) in raws.iter().enumerate() {
+                let expanded = config.expand_path(raw, xdg)?;
+                let path = match kind {
+                    ConfigValueKind::Builder | ConfigValueKind::BuilderSteps => {
+                        Path::new(&config.get_builders_path_prefix()).join(&expanded)
+                    }
+                    ConfigValueKind::Opener => {
+                        Path::new(&config.get_openers_path_prefix()).join(&expanded)
+                    }
+                    _ => to_full_path(&expanded),
+                };
+                let path = path.to_str().unwrap().to_string();
+                // keep paths written with variables portable when they are saved
+                if expanded != *raw {
+                    let mut key = key.clone();
+                    if raws.len() > 1 {
+                        key.push(i.to_string());
+                    }
+                    config.keep_portable_path(&key, raw, &path);
+                }
+                paths.push(toml::Value::String(path));
+            }
+            match paths.len() {
+                1 => paths.remove(0),
+                _ => toml::Value::Array(paths),
+            }
+        }
+    };
+
+    let old_config = config.clone();
+    config.set_value(&key, Some(value))?;
+    check_config_change(&key, &old_config, &config, xdg)?;
+    config.save(None, xdg)?;
+    Ok(())
+}
+
+/// Remove a config key, e.g. `project_types.rust.builder`, so its default is used.
+///
+/// # Arguments
+/// - `key` – Dotted path of the key.
+/// - `xdg` – XDG configuration reference.
+pub fn unset_config_value(key: &str, xdg: &XDG) -> Result<(), errors::ConfigValueError> {
+    let key = parse_config_key(key)?;
+    if Config::get_key_kind(&key).is_none() {
+        Err(errors::InvalidConfigKeyError(format!(
+            "{} is not a config key that can be unset",
+            format_config_key(&key)
+        )))?;
+    }
+    let _lock = Config::lock(xdg)?;
+    let mut config = Config::load(None, xdg)?;
+    let old_config = config.clone();
+    config.set_value(&key, None)?;
+    check_config_change(&key, &old_config, &config, xdg)?;
+    config.save(None, xdg)?;
+    Ok(())
+}
+
+/// Get every config key that can be set, used for completion.
+///
+/// # Arguments
+/// - `xdg` – XDG configuration reference.
+pub fn get_config_keys(xdg: &XDG) -> Result<Vec<String>, errors::ConfigError> {
+    let config = Config::load(None, xdg)?;
+    Ok(config
+        .get_keys()
+        .iter()
+        .map(|key| format_config_key(key))
+        .collect())
+}
+
+/// Environment variable that selects a profile when none is given explicitly
+pub const PROFILE_ENV_VAR: &str = "DONNA_PROFILE";
+
+fn profile_exists(name: &str, xdg: &XDG) -> bool {
+    name == XDG::DEFAULT_PROFILE || Config::get_profiles_dir(xdg).join(name).is_dir()
+}
+
+/// Get the names of all profiles, the default profile is always first.
+///
+/// # Arguments
+/// - `xdg` – XDG configuration reference.
+pub fn get_profiles(xdg: &XDG) -> Result<Vec<String>, errors::GetProfilesError> {
+    let mut profiles = Vec::new();
+    let profiles_dir = Config::get_profiles_dir(xdg);
+    if profiles_dir.is_dir() {
+        for entry in profiles_dir.read_dir()? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                profiles.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    profiles.sort();
+    profiles.insert(0, XDG::DEFAULT_PROFILE.to_string());
+    Ok(profiles)
+}
+
+/// Create a named profile with its own config and default library. Builders and openers are shared with the other
+/// profiles unless `separate_scripts` is set.
+///
+/// # Arguments
+/// - `name` – The name of the profile.
+/// - `separate_scripts` – Give the profile its own builders and openers directories.
+/// - `xdg` – XDG configuration reference.
+pub fn create_profile(
+    name: &str,
+    separate_scripts: bool,
+    xdg: &XDG,
+) -> Result<(), errors::CreateProfileError> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        Err(errors::InvalidProfileNameError(format!(
+            "'{name}' can't be used as a profile name"
+        )))?;
+    }
+    if profile_exists(name, xdg) {
+        Err(errors::ProfileExistsError(format!(
+            "Profile {name} already exists"
+        )))?;
+    }
+
+    let profile_xdg = xdg.clone().with_profile(Some(name));
+    let config_path = Config::get_path(&profile_xdg);
+    fs::create_dir_all(config_path.parent().unwrap())?;
+    fs::File::create(&config_path)?;
+    let lib_path = Config::get_default_lib_path(&profile_xdg);
+    fs::create_dir_all(&lib_path)?;
+
+    let mut config = Config::load(None, &profile_xdg)?;
+    if separate_scripts {
+        let data_dir = lib_path.parent().unwrap();
+        let builders_dir = data_dir.join("builders");
+        let openers_dir = data_dir.join("openers");
+        fs::create_dir_all(&builders_dir)?;
+        fs::create_dir_all(&openers_dir)?;
+        config.set_builders_path_prefix(builders_dir.to_str().unwrap());
+        config.set_openers_path_prefix(openers_dir.to_str().unwrap());
+    }
+    config.save(None, &profile_xdg)?;
+    Ok(())
+}
+
+/// Set the profile that is used when none is given with `--profile` or `DONNA_PROFILE`.
+///
+/// # Arguments
+/// - `name` – The name of the profile, "default" uses the default config.
+/// - `xdg` – XDG configuration reference.
+pub fn switch_profile(name: &str, xdg: &XDG) -> Result<(), errors::SwitchProfileError> {
+    if !profile_exists(name, xdg) {
+        Err(errors::ProfileNotFoundError(format!(
+            "Profile {name} does not exist"
+        )))?;
+    }
+    let mut profiles_config = ProfilesConfig::load(xdg)?;
+    profiles_config.default = Some(name)
+        .filter(|name| *name != XDG::DEFAULT_PROFILE)
+        .map(str::to_string);
+    profiles_config.save(xdg)?;
+    Ok(())
+}
+
+/// Get the profile to use, the first one set of the given profile, the `DONNA_PROFILE` environment variable and
+/// the profile set with `switch_profile`.
+///
+/// # Arguments
+/// - `profile` – Profile given explicitly, e.g. with `--profile`.
+/// - `xdg` – XDG configuration reference.
+/// # Returns
+/// - The name of the profile, or None if the default config should be used.
+pub fn resolve_profile(
+    profile: Option<&str>,
+    xdg: &XDG,
+) -> Result<Option<String>, errors::ResolveProfileError> {
+    let profile = match profile {
+        Some(profile) => Some(profile.to_string()),
+        None => match std::env::var(PROFILE_ENV_VAR) {
+            Ok(profile) if !profile.is_empty() => Some(profile),
+            _ => ProfilesConfig::load(xdg)?.default,
+        },
+    };
+    if let Some(name) = profile.as_deref() {
+        if !profile_exists(name, xdg) {
+            Err(errors::ProfileNotFoundError(format!(
+                "Profile {name} does not exist"
+            )))?;
+        }
+    }
+    Ok(profile.filter(|name| name != XDG::DEFAULT_PROFILE))
+}
+
+/// Name of the example builder and opener installed by `init`
+pub const EXAMPLE_SCRIPT_NAME: &str = "example.lua";
+const EXAMPLE_BUILDER: &str = include_str!("templates/example_builder.lua");
+const EXAMPLE_OPENER: &str = include_str!("templates/example_opener.lua");
+
+/// Set donna up: create the config and data directories and a commented starter config, register folders as
+/// libraries and alias groups, and install an example builder and opener. Folders that don't exist yet are
+/// created. Running it again only adds what is missing.
+///
+/// # Arguments
+/// - `libraries` – Libraries to register as (name, path).
+/// - `alias_groups` – Alias groups to register as (name, path).
+/// - `examples` – Install `example.lua` in the builders and openers directories if it isn't there yet.
+/// - `xdg` – XDG configuration reference.
+pub fn init(
+    libraries: &[(&str, &str)],
+    alias_groups: &[(&str, &str)],
+    examples: bool,
+    xdg: &XDG,
+) -> Result<(), errors::InitError> {
+    setup_pm(xdg)?;
+    for (name, path) in libraries {
+        create_lib(name, path, false, to_full_path(path).exists(), None, xdg)?;
+    }
+    for (name, path) in alias_groups {
+        create_alias_group(name, path, to_full_path(path).exists(), xdg)?;
+    }
+    if examples {
+        let config = Config::load(None, xdg)?;
+        for (dir, script) in [
+            (config.get_builders_path_prefix(), EXAMPLE_BUILDER),
+            (config.get_openers_path_prefix(), EXAMPLE_OPENER),
+        ] {
+            let path = Path::new(&dir).join(EXAMPLE_SCRIPT_NAME);
+            if !path.exists() {
+                fs::create_dir_all(&dir)?;
+                fs::write(&path, script)?;
+                log::info!("Installed example script at {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read every file under a directory by its path relative to the directory, with the permissions of executables.
+fn read_scripts(dir: &Path) -> Result<BTreeMap<String, ExportedScript>, std::io::Error> {
+    let mut scripts = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(scripts);
+    }
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        for entry in current.read_dir()? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let rel_path = path
+                .strip_prefix(dir)
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let contents = fs::read(&path)
+                .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+            #[cfg(unix)]
+            let mode = {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = fs::metadata(&path)?.permissions().mode() & 0o777;
+                (mode & 0o111 != 0).then_some(mode)
+            };
+            #[cfg(not(unix))]
+            let mode = None;
+            let script = match (String::from_utf8(contents), mode) {
+                (Ok(text), None) => ExportedScript::Text(text),
+                (Ok(text), mode) => ExportedScript::File {
+                    text: Some(text),
+                    base64: None,
+                    mode,
+                },
+                (Err(e), mode) => ExportedScript::File {
+                    text: None,
+                    base64: Some(BASE64_STANDARD.encode(e.into_bytes())),
+                    mode,
+                },
+            };
+            scripts.insert(rel_path, script);
+        }
+    }
+    Ok(scripts)
+}
+
+/// Whether a relative path stays inside the directory it is joined to
+fn is_inside(rel_path: &Path) -> bool {
+    rel_path
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+}
+
+/// Whether a name is a single path component that stays inside the directory it is joined to
+fn is_plain_name(name: &str) -> bool {
+    let path = Path::new(name);
+    path.components().count() == 1 && is_inside(path)
+}
+
+/// Write scripts read with `read_scripts` back under a directory, paths outside of the directory are skipped.
+fn write_scripts(
+    dir: &Path,
+    scripts: &BTreeMap<String, ExportedScript>,
+) -> Result<(), std::io::Error> {
+    for (rel_path, script) in scripts {
+        let rel_path = Path::new(rel_path);
+        if !is_inside(rel_path) {
+            log::warn!(
+                "Skipping script {}, it is outside of {}",
+                rel_path.display(),
+                dir.display()
+            );
+            continue;
+        }
+        let path = dir.join(rel_path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        let mode = match script {
+            // exports from before permissions were kept only have text, scripts with a shebang are executables
+            ExportedScript::Text(text) => {
+                fs::write(&path, text)?;
+                text.starts_with("#!").then_some(0o755)
+            }
+            ExportedScript::File { text, base64, mode } => {
+                let contents = match (text, base64) {
+                    (Some(text), _) => text.clone().into_bytes(),
+                    (None, Some(base64)) => BASE64_STANDARD
+                        .decode(base64)
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+                    (None, None) => Vec::new(),
+                };
+                fs::write(&path, contents)?;
+                *mode
+            }
+        };
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+    }
+    Ok(())
+}
+
+/// Get the URL of the `origin` remote of a git repository, None if the project isn't a repository or has no remote
+fn get_git_remote(project_path: &Path) -> Option<String> {
+    if !project_path.join(".git").exists() {
+        return None;
+    }
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(project_path)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    let remote = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !remote.is_empty()).then_some(remote)
+}
+
+/// Export everything needed to set donna up on another machine to a TOML file: the user's config, the builder
+/// and opener scripts, and every project with its library, config and git remote. Paths under the home and XDG
+/// directories are written with `~` and variables. Shared config files from `include` and `config.d` are not
+/// exported.
+///
+/// # Arguments
+/// - `path` – File to write the export to.
+/// - `xdg` – XDG configuration reference.
+pub fn export_state(path: &str, xdg: &XDG) -> Result<(), errors::ExportError> {
+    let config = Config::load(None, xdg)?;
+    let mut state = StateExport {
+        version: StateExport::VERSION,
+        config: Config::load_portable(xdg)?,
+        builders: read_scripts(Path::new(&config.get_builders_path_prefix()))?,
+        openers: read_scripts(Path::new(&config.get_openers_path_prefix()))?,
+        projects: Vec::new(),
+    };
+
+    for (name, (_, lib, project_path)) in get_projects(xdg)? {
+        let project_path = Path::new(&project_path);
+        let project_config_path = project_path.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+        let mut project_config = ProjectConfig::load(project_config_path.to_str().unwrap())?;
+        let builder_steps = project_config
+            .builder
+            .iter_mut()
+            .flat_map(Builder::steps_mut);
+        for script in builder_steps.chain(project_config.opener.iter_mut()) {
+            *script = Config::to_portable_path(script, xdg);
+        }
+        state.projects.push(ExportedProject {
+            name,
+            library: lib,
+            remote: get_git_remote(project_path),
+            config: project_config,
+        });
+    }
+    state
+        .projects
+        .sort_by(|a, b| (&a.library, &a.name).cmp(&(&b.library, &b.name)));
+    state.save(&to_full_path(path))?;
+    Ok(())
+}
+
+/// What happened to a project when it was restored
+#[derive(Debug, PartialEq)]
+pub enum ProjectRestoreStatus {
+    /// Cloned from its git remote
+    Cloned,
+    /// The project directory already existed
+    AlreadyExists,
+    /// The project has no git remote, its files need to be copied over by hand
+    NoRemote,
+    /// Cloning failed, e.g. because the remote is unreachable
+    CloneFailed,
+}
+
+/// Whether the config tracks anything besides the default library
+fn is_fresh_config(config: &Config) -> bool {
+    config.get_libs().unwrap_or_default().len() <= 1
+        && config.get_alias_groups().unwrap_or_default().is_empty()
+        && config.get_project_types().unwrap_or_default().is_empty()
+}
+
+/// Set donna up from a file written by `export_state`. The config and scripts are written, libraries and alias
+/// groups are created, projects with a git remote are cloned and every project is linked into its alias groups.
+/// Projects that already exist are linked but not changed.
+///
+/// # Arguments
+/// - `path` – The export file.
+/// - `force` – Replace the current config even if it already tracks libraries, alias groups or project types.
+/// - `xdg` – XDG configuration reference.
+/// # Returns
+/// - Each project with what happened to it, in the order they were exported
+pub fn restore_state(
+    path: &str,
+    force: bool,
+    xdg: &XDG,
+) -> Result<Vec<(String, ProjectRestoreStatus)>, errors::RestoreError> {
+    let state = StateExport::load(&to_full_path(path))?;
+    // project names and libraries end up in paths, check them before anything is written
+    for project in &state.projects {
+        for name in [&project.name, &project.library] {
+            if !is_plain_name(name) {
+                Err(errors::InvalidExportError(format!(
+                    "Project {} in library {} is not a plain name",
+                    project.name, project.library
+                )))?;
+            }
+        }
+    }
+    setup_pm(xdg)?;
+    let config = {
+        let _lock = Config::lock(xdg)?;
+        let config_path = Config::get_path(xdg);
+        let original_contents = fs::read_to_string(&config_path)?;
+        if !force && !is_fresh_config(&Config::load(None, xdg)?) {
+            Err(errors::AlreadySetUpError(
+                "The config already tracks libraries, alias groups or project types, use --force to replace it"
+                    .to_string(),
+            ))?;
+        }
+        if !original_contents.trim().is_empty() {
+            let mut file_name = config_path.file_name().unwrap_or_default().to_owned();
+            file_name.push(".bak");
+            let backup_path = config_path.with_file_name(file_name);
+            fs::write(&backup_path, original_contents)?;
+            log::info!(
+                "Backed up {} to {} before restoring",
+                config_path.display(),
+                backup_path.display()
+            );
+        }
+        utils::atomic_write(
+            &config_path,
+            toml::to_string(&state.config).map_err(errors::ConfigError::from)?,
+        )?;
+        Config::load(None, xdg)?
+    };
+
+    for lib_path in config.get_libs().unwrap_or_default().values() {
+        fs::create_dir_all(lib_path)?;
+    }
+    for alias_group in config.get_alias_groups().unwrap_or_default().values() {
+        fs::create_dir_all(&alias_group.path)?;
+    }
+    write_scripts(
+        Path::new(&config.get_builders_path_prefix()),
+        &state.builders,
+    )?;
+    write_scripts(Path::new(&config.get_openers_path_prefix()), &state.openers)?;
+
+    let mut restored = Vec::new();
+    for project in state.projects {
+        let lib_path =
+            config
+                .get_lib_path(Some(&project.library))
+                .ok_or(errors::LibNotTrackedError(format!(
+                    "Library {} of project {} does not exist",
+                    project.library, project.name
+                )))?;
+        let project_path = Path::new(lib_path).join(&project.name);
+        let status = match &project.remote {
+            _ if project_path.exists() => ProjectRestoreStatus::AlreadyExists,
+            None => ProjectRestoreStatus::NoRemote,
+            Some(remote) => {
+                let mut command = std::process::Command::new("git");
+                command.arg("clone").arg(remote).arg(&project_path);
+                log::info!("Running git clone: {command:?}");
+                if command.status()?.success() {
+                    ProjectRestoreStatus::Cloned
+                } else {
+                    ProjectRestoreStatus::CloneFailed
+                }
+            }
+        };
+        if matches!(
+            status,
+            ProjectRestoreStatus::NoRemote | ProjectRestoreStatus::CloneFailed
+        ) {
+            restored.push((project.name, status));
+            continue;
+        }
+
+        let project_config_path = project_path.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+        if status == ProjectRestoreStatus::Cloned || !project_config_path.exists() {
+            let mut project_config = project.config;
+            let builder_steps = project_config
+                .builder
+                .iter_mut()
+                .flat_map(Builder::steps_mut);
+            for script in builder_steps.chain(project_config.opener.iter_mut()) {
+                *script = config.expand_path(script, xdg)?;
+            }
+            fs::create_dir_all(project_config_path.parent().unwrap())?;
+            project_config.save(project_config_path.to_str().unwrap())?;
+        }
+        let project_config = ProjectConfig::load(project_config_path.to_str().unwrap())?;
+        for alias_group in project_config.tracked_alias_groups.iter().flatten() {
+            let Some(group) = config.get_alias_group(alias_group) else {
+                log::warn!(
+                    "Alias group {alias_group} of project {} does not exist",
+                    project.name
+                );
+                continue;
+            };
+            let alias_path = Path::new(&group.path).join(&project.name);
+            if fs::symlink_metadata(&alias_path).is_err() {
+                create_link(
+                    &project_path,
+                    &alias_path,
+                    config.uses_relative_links(alias_group),
+                )?;
+            }
+        }
+        restored.push((project.name, status));
+    }
+    Ok(restored)
+}
+
 // BLOCKED: need to track aliases for each project in the project config since the system doesn't track it
 // pub fn set_project_alias_groups(name: &str, lib: Option<api_types::LibraryName>, alias_groups: Vec<String>, xdg: &XDG) {
 //     let mut config = Config::load(None, xdg).expect("Could not load config");
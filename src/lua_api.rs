@@ -0,0 +1,628 @@
+//! The `donna` table available to builders and openers.
+//!
+//! Every Lua state donna runs a script in gets a global `donna` table whose functions call the same library
+//! functions as the CLI, so scripts can look up projects and create, link or open them:
+//!
+//! - `donna.projects()` – list of `{name, type, library, path}` for every project
+//! - `donna.project(name, library)` – `{name, type, library, path, alias_groups, builder, opener}` or nil,
+//!   `library` is optional, without it every library is searched
+//! - `donna.alias_groups()` – table of alias group name to path
+//! - `donna.libraries()` – table of library name to path
+//! - `donna.create_project{name, type, library, alias_groups, git_clone, already_exists, args, params}` – only
+//!   `name` is required, `params` answers the builder's questions like `--param`
+//! - `donna.add_to_group(project, alias_group, library)` – `library` is optional
+//! - `donna.open(project, library, args)` – `library` and the list of script arguments are optional
+//! - `donna.exec{cmd, args..., cwd = , env = , capture = true}` – run a command without a shell and wait for it,
+//!   returns `{code, stdout, stderr}`, the output is only captured if `capture` isn't false
+//! - `donna.spawn{cmd, args..., cwd = , env = }` – start a command without waiting for it, e.g. an editor, and
+//!   return its process id
+//!
+//! - `donna.prompt(text, default, name)` – ask for a line of text
+//! - `donna.confirm(text, default, name)` – ask a yes or no question
+//! - `donna.select(text, options, default, name)` – ask to choose one of a list of options
+//!
+//! Questions are answered by `--param` values, `default` and `name` are optional, see `prompts`. Projects created
+//! or opened by a script keep the `--param` values and `--yes` the script was run with.
+//!
+//! Commands run in the project directory by default, a relative `cwd` is relative to it. `env` adds to the
+//! environment donna was run with.
+//!
+//! Errors are raised as Lua errors.
+//!
+//! Scripts also get a `PM` table describing what they are run for, see `ScriptContext`, and the older `PM_*`
+//! globals for compatibility.
+//!
+//! Before the script itself, every Lua state runs the `lua_init` script from the config if it exists, and
+//! `require` finds modules in the `lua` directories next to the builders and openers directories, so helpers can
+//! be shared between scripts.
+
+use crate::prompts;
+use crate::utils::XDG;
+use crate::{
+    add_to_alias_group, create_project, get_alias_groups, get_libraries, get_projects,
+    open_project, Builder, Config, ProjectConfig,
+};
+use mlua::{IntoLua, Lua, Table};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Name of the global table the API is available under
+pub const MODULE_NAME: &str = "donna";
+
+/// Name of the global table the script context is available under
+pub const CONTEXT_NAME: &str = "PM";
+
+/// Extra arguments passed to a builder or opener, e.g. from `donna open project x -- --new-window`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptArgs {
+    pub args: Vec<String>,
+    /// Answers to the questions the script asks by question name, from `--param name=value`
+    pub params: BTreeMap<String, String>,
+    /// Whether questions are answered without asking, from `--yes`
+    pub assume_yes: bool,
+}
+
+impl ScriptArgs {
+    pub fn new(args: Vec<String>) -> ScriptArgs {
+        ScriptArgs {
+            args,
+            ..Default::default()
+        }
+    }
+
+    /// Set the answers to the questions the script asks
+    pub fn with_params(mut self, params: impl IntoIterator<Item = (String, String)>) -> ScriptArgs {
+        self.params = params.into_iter().collect();
+        self
+    }
+
+    /// Set whether questions are answered without asking
+    pub fn with_assume_yes(mut self, assume_yes: bool) -> ScriptArgs {
+        self.assume_yes = assume_yes;
+        self
+    }
+
+    /// Get the arguments of a script run by this one, e.g. with `donna.create_project`. It keeps the answers and
+    /// `--yes` of this run, `params` are added to the answers.
+    pub fn nested(&self, args: Vec<String>, params: BTreeMap<String, String>) -> ScriptArgs {
+        let mut nested_params = self.params.clone();
+        nested_params.extend(params);
+        ScriptArgs::new(args)
+            .with_params(nested_params)
+            .with_assume_yes(self.assume_yes)
+    }
+
+    /// Get the `--key=value` arguments as key and value, and `--flag` arguments as flag and None. Anything after
+    /// a `--` argument isn't an option.
+    pub fn options(&self) -> BTreeMap<String, Option<String>> {
+        self.args
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .filter_map(|arg| {
+                let option = arg.strip_prefix("--").filter(|option| !option.is_empty())?;
+                Some(match option.split_once('=') {
+                    Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                    None => (option.to_string(), None),
+                })
+            })
+            .collect()
+    }
+}
+
+/// What triggered a script, `PM.action` in the script
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptAction {
+    /// A builder run for a new project
+    Build,
+    /// An opener run for a project
+    Open,
+    /// The config opener
+    OpenConfig,
+    /// The builders directory opener
+    OpenBuilders,
+    /// The openers directory opener
+    OpenOpeners,
+    /// The `on_delete` hook of a project's type
+    Delete,
+    /// The `on_move` hook of a project's type
+    Move,
+    /// The `on_rename` hook of a project's type
+    Rename,
+    /// The `on_archive` hook of a project's type
+    Archive,
+    /// The `on_add_to_group` hook of a project's type
+    AddToGroup,
+    /// The `on_close` hook of a project's type
+    Close,
+}
+
+impl ScriptAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptAction::Build => "build",
+            ScriptAction::Open => "open",
+            ScriptAction::OpenConfig => "open_config",
+            ScriptAction::OpenBuilders => "open_builders",
+            ScriptAction::OpenOpeners => "open_openers",
+            ScriptAction::Delete => "delete",
+            ScriptAction::Move => "move",
+            ScriptAction::Rename => "rename",
+            ScriptAction::Archive => "archive",
+            ScriptAction::AddToGroup => "add_to_group",
+            ScriptAction::Close => "close",
+        }
+    }
+}
+
+/// Everything a script is told about what it is run for. Project fields are None for the config, builders and
+/// openers openers.
+#[derive(Debug, Clone)]
+pub struct ScriptContext {
+    pub action: ScriptAction,
+    /// Absolute path of the project, or of the config file or directory the opener is run for
+    pub path: String,
+    pub name: Option<String>,
+    pub library: Option<String>,
+    pub library_path: Option<String>,
+    pub project_type: Option<String>,
+    /// Path of the link in each alias group of the project by alias group name
+    pub alias_groups: BTreeMap<String, String>,
+    pub builder: Option<Builder>,
+    pub opener: Option<String>,
+    pub metadata: Option<toml::Table>,
+    pub args: ScriptArgs,
+    /// What a hook's operation applies to, the new path of the project for `on_move` and `on_archive`, the new name
+    /// for `on_rename` and the alias group for `on_add_to_group`
+    pub target: Option<String>,
+}
+
+impl ScriptContext {
+    /// Context of a script run for a project in a library
+    pub fn for_project(
+        action: ScriptAction,
+        name: &str,
+        project_path: &Path,
+        lib: Option<&str>,
+        project_config: &ProjectConfig,
+        alias_groups: &[&str],
+        config: &Config,
+    ) -> ScriptContext {
+        let alias_groups = alias_groups
+            .iter()
+            .filter_map(|group| {
+                let alias = config.get_alias_group(group)?;
+                let link = Path::new(&alias.path).join(name);
+                Some((group.to_string(), link.to_str().unwrap().to_string()))
+            })
+            .collect();
+        ScriptContext {
+            action,
+            path: project_path.to_str().unwrap().to_string(),
+            name: Some(name.to_string()),
+            library: Some(config.get_lib_name(lib).to_string()),
+            library_path: config.get_lib_path(lib).map(str::to_string),
+            project_type: project_config.project_type.clone(),
+            alias_groups,
+            builder: project_config.builder.clone(),
+            opener: project_config.opener.clone(),
+            metadata: project_config.metadata.clone(),
+            args: ScriptArgs::default(),
+            target: None,
+        }
+    }
+
+    /// Context of an opener run for the config file, the builders or the openers directory
+    pub fn for_path(action: ScriptAction, path: &str) -> ScriptContext {
+        ScriptContext {
+            action,
+            path: path.to_string(),
+            name: None,
+            library: None,
+            library_path: None,
+            project_type: None,
+            alias_groups: BTreeMap::new(),
+            builder: None,
+            opener: None,
+            metadata: None,
+            args: ScriptArgs::default(),
+            target: None,
+        }
+    }
+
+    /// Set the extra arguments the script is run with
+    pub fn with_args(mut self, args: &ScriptArgs) -> ScriptContext {
+        self.args = args.clone();
+        self
+    }
+
+    /// Set what a hook's operation applies to
+    pub fn with_target(mut self, target: Option<&str>) -> ScriptContext {
+        self.target = target.map(str::to_string);
+        self
+    }
+
+    /// Get the directory scripts run in, the project directory or the directory of the file an opener is run for
+    pub fn working_dir(&self) -> &Path {
+        let path = Path::new(&self.path);
+        match path.is_dir() {
+            true => path,
+            false => path.parent().unwrap_or(path),
+        }
+    }
+
+    /// Set the `PM` table and the `PM_*` globals older scripts use
+    pub fn set_globals(&self, lua: &Lua) -> mlua::Result<()> {
+        let globals = lua.globals();
+        let context = lua.create_table()?;
+        context.set("action", self.action.as_str())?;
+        context.set("version", env!("CARGO_PKG_VERSION"))?;
+        context.set("path", self.path.as_str())?;
+        context.set("name", self.name.as_deref())?;
+        context.set("library", self.library.as_deref())?;
+        context.set("library_path", self.library_path.as_deref())?;
+        context.set("type", self.project_type.as_deref())?;
+        context.set("alias_groups", self.alias_groups.clone())?;
+        context.set("builder", self.builder.clone())?;
+        context.set("opener", self.opener.as_deref())?;
+        context.set("target", self.target.as_deref())?;
+        let metadata = match &self.metadata {
+            Some(metadata) => toml_to_lua(lua, &toml::Value::Table(metadata.clone()))?,
+            None => mlua::Value::Nil,
+        };
+        context.set("metadata", metadata)?;
+        let options = lua.create_table()?;
+        for (key, value) in self.args.options() {
+            match value {
+                Some(value) => options.set(key, value)?,
+                None => options.set(key, true)?,
+            }
+        }
+        context.set("args", self.args.args.clone())?;
+        context.set("options", options.clone())?;
+        context.set("params", self.args.params.clone())?;
+        context.set("assume_yes", self.args.assume_yes)?;
+        globals.set(CONTEXT_NAME, context)?;
+        globals.set("PM_ARGS", self.args.args.clone())?;
+        globals.set("PM_OPTIONS", options)?;
+
+        match self.action {
+            ScriptAction::Build
+            | ScriptAction::Open
+            | ScriptAction::Delete
+            | ScriptAction::Move
+            | ScriptAction::Rename
+            | ScriptAction::Archive
+            | ScriptAction::AddToGroup
+            | ScriptAction::Close => {
+                globals.set("PM_PROJECT_NAME", self.name.as_deref())?;
+                globals.set("PM_PROJECT_PATH", self.path.as_str())?;
+                globals.set("PM_PROJECT_TYPE", self.project_type.as_deref())?;
+                globals.set("PM_PROJECT_LIB", self.library.as_deref())?;
+                globals.set(
+                    "PM_ALIAS_GROUP",
+                    self.alias_groups.keys().next().map(String::as_str),
+                )?;
+            }
+            ScriptAction::OpenConfig => globals.set("PM_CONFIG_PATH", self.path.as_str())?,
+            ScriptAction::OpenBuilders => globals.set("PM_BUILDERS_PATH", self.path.as_str())?,
+            ScriptAction::OpenOpeners => globals.set("PM_OPENERS_PATH", self.path.as_str())?,
+        }
+        Ok(())
+    }
+}
+
+/// A builder with one script is its path, one with steps a list of their paths
+impl IntoLua for Builder {
+    fn into_lua(self, lua: &Lua) -> mlua::Result<mlua::Value> {
+        match self {
+            Builder::Script(script) => script.into_lua(lua),
+            Builder::Steps(steps) => steps.into_lua(lua),
+        }
+    }
+}
+
+fn toml_to_lua(lua: &Lua, value: &toml::Value) -> mlua::Result<mlua::Value> {
+    Ok(match value {
+        toml::Value::String(value) => mlua::Value::String(lua.create_string(value)?),
+        toml::Value::Integer(value) => mlua::Value::Integer(*value),
+        toml::Value::Float(value) => mlua::Value::Number(*value),
+        toml::Value::Boolean(value) => mlua::Value::Boolean(*value),
+        toml::Value::Datetime(value) => mlua::Value::String(lua.create_string(value.to_string())?),
+        toml::Value::Array(values) => {
+            let table = lua.create_table()?;
+            for value in values {
+                table.push(toml_to_lua(lua, value)?)?;
+            }
+            mlua::Value::Table(table)
+        }
+        toml::Value::Table(values) => {
+            let table = lua.create_table()?;
+            for (key, value) in values {
+                table.set(key.as_str(), toml_to_lua(lua, value)?)?;
+            }
+            mlua::Value::Table(table)
+        }
+    })
+}
+
+/// Create a Lua state with the `donna` table, the script context and the Lua module directories set, and run the
+/// `lua_init` script in it. Errors are the errors of the init script.
+pub fn new_lua(context: &ScriptContext, config: &Config, xdg: &XDG) -> mlua::Result<Lua> {
+    let lua = Lua::new();
+    let module = create_module(&lua, context, xdg)?;
+    lua.globals().set(MODULE_NAME, module)?;
+    context.set_globals(&lua)?;
+    set_module_dirs(&lua, config)?;
+
+    // only the default init script is optional, one set with `lua_init` has to exist
+    let init = config.get_lua_init();
+    match fs::read_to_string(&init) {
+        Ok(contents) => lua.load(contents).set_name(format!("@{init}")).exec()?,
+        Err(e) if e.kind() == ErrorKind::NotFound && init == Config::get_default_lua_init(xdg) => {}
+        Err(e) => return Err(mlua::Error::external(e)),
+    }
+    Ok(lua)
+}
+
+/// Add the module directories of the config in front of `package.path`
+fn set_module_dirs(lua: &Lua, config: &Config) -> mlua::Result<()> {
+    let package: Table = lua.globals().get("package")?;
+    let mut path = String::new();
+    for dir in config.get_lua_module_dirs() {
+        let dir = dir.to_str().unwrap();
+        path.push_str(&format!("{dir}/?.lua;{dir}/?/init.lua;"));
+    }
+    path.push_str(&package.get::<String>("path")?);
+    package.set("path", path)
+}
+
+/// Build a command from a table of the command, its arguments and the `cwd` and `env` options. Relative `cwd`s
+/// are relative to `dir`, which is also the default.
+fn table_to_command(name: &str, options: &Table, dir: &Path) -> mlua::Result<Command> {
+    let mut args = options.sequence_values::<String>();
+    let program = args
+        .next()
+        .ok_or_else(|| mlua::Error::runtime(format!("donna.{name} needs a command")))??;
+    let mut command = Command::new(program);
+    for arg in args {
+        command.arg(arg?);
+    }
+    let cwd: Option<String> = options.get("cwd")?;
+    command.current_dir(dir.join(cwd.unwrap_or_default()));
+    let env: Option<HashMap<String, String>> = options.get("env")?;
+    command.envs(env.unwrap_or_default());
+    Ok(command)
+}
+
+fn command_error(command: &Command, error: std::io::Error) -> mlua::Error {
+    mlua::Error::runtime(format!(
+        "Failed to run {}: {error}",
+        command.get_program().to_string_lossy()
+    ))
+}
+
+fn create_module(lua: &Lua, context: &ScriptContext, xdg: &XDG) -> mlua::Result<Table> {
+    let module = lua.create_table()?;
+
+    let xdg_ = xdg.clone();
+    module.set(
+        "projects",
+        lua.create_function(move |lua, ()| {
+            let mut projects: Vec<_> = get_projects(&xdg_)
+                .map_err(mlua::Error::external)?
+                .into_iter()
+                .collect();
+            projects.sort();
+            let list = lua.create_table()?;
+            for (name, (project_type, lib, path)) in projects {
+                let project = lua.create_table()?;
+                project.set("name", name)?;
+                project.set("type", (!project_type.is_empty()).then_some(project_type))?;
+                project.set("library", lib)?;
+                project.set("path", path)?;
+                list.push(project)?;
+            }
+            Ok(list)
+        })?,
+    )?;
+
+    let xdg_ = xdg.clone();
+    module.set(
+        "project",
+        lua.create_function(move |lua, (name, library): (String, Option<String>)| {
+            // without a library every library is searched
+            let (lib, path) = match library {
+                Some(library) => {
+                    let libraries = get_libraries(&xdg_).map_err(mlua::Error::external)?;
+                    let lib_path = libraries.get(&library).ok_or_else(|| {
+                        mlua::Error::runtime(format!("Library {library} does not exist"))
+                    })?;
+                    let path = Path::new(lib_path).join(&name);
+                    (library, path.to_str().unwrap().to_string())
+                }
+                None => {
+                    let projects = get_projects(&xdg_).map_err(mlua::Error::external)?;
+                    let Some((_, lib, path)) = projects.get(&name) else {
+                        return Ok(None);
+                    };
+                    (lib.clone(), path.clone())
+                }
+            };
+            let project_config_path = Path::new(&path).join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+            if !project_config_path.exists() {
+                return Ok(None);
+            }
+            let project_config = ProjectConfig::load(project_config_path.to_str().unwrap())
+                .map_err(mlua::Error::external)?;
+            let project = lua.create_table()?;
+            project.set("name", name)?;
+            project.set("type", project_config.project_type)?;
+            project.set("library", lib)?;
+            project.set("path", path)?;
+            project.set(
+                "alias_groups",
+                project_config.tracked_alias_groups.unwrap_or_default(),
+            )?;
+            project.set("builder", project_config.builder)?;
+            project.set("opener", project_config.opener)?;
+            Ok(Some(project))
+        })?,
+    )?;
+
+    let xdg_ = xdg.clone();
+    module.set(
+        "alias_groups",
+        lua.create_function(move |_, ()| {
+            Ok(get_alias_groups(&xdg_)
+                .map_err(mlua::Error::external)?
+                .into_iter()
+                .map(|(name, group)| (name, group.path))
+                .collect::<HashMap<_, _>>())
+        })?,
+    )?;
+
+    let xdg_ = xdg.clone();
+    module.set(
+        "libraries",
+        lua.create_function(move |_, ()| get_libraries(&xdg_).map_err(mlua::Error::external))?,
+    )?;
+
+    let xdg_ = xdg.clone();
+    let parent_args = context.args.clone();
+    module.set(
+        "create_project",
+        lua.create_function(move |_, options: Table| {
+            let name: String = options.get("name")?;
+            let project_type: Option<String> = options.get("type")?;
+            let library: Option<String> = options.get("library")?;
+            let alias_groups: Option<Vec<String>> = options.get("alias_groups")?;
+            let git_clone: Option<String> = options.get("git_clone")?;
+            let already_exists: Option<bool> = options.get("already_exists")?;
+            let args: Option<Vec<String>> = options.get("args")?;
+            let params: Option<BTreeMap<String, String>> = options.get("params")?;
+            let alias_groups: Option<Vec<&str>> = alias_groups
+                .as_ref()
+                .map(|groups| groups.iter().map(String::as_str).collect());
+            create_project(
+                &name,
+                project_type.as_deref(),
+                alias_groups.as_deref(),
+                library.as_deref(),
+                already_exists.unwrap_or(false),
+                git_clone.as_deref(),
+                &parent_args.nested(args.unwrap_or_default(), params.unwrap_or_default()),
+                &xdg_,
+            )
+            .map_err(mlua::Error::external)
+        })?,
+    )?;
+
+    let xdg_ = xdg.clone();
+    module.set(
+        "add_to_group",
+        lua.create_function(
+            move |_, (project, alias_group, library): (String, String, Option<String>)| {
+                add_to_alias_group(&project, &alias_group, library.as_deref(), &xdg_)
+                    .map_err(mlua::Error::external)
+            },
+        )?,
+    )?;
+
+    let xdg_ = xdg.clone();
+    let parent_args = context.args.clone();
+    module.set(
+        "open",
+        lua.create_function(
+            move |_, (project, library, args): (String, Option<String>, Option<Vec<String>>)| {
+                let args = parent_args.nested(args.unwrap_or_default(), BTreeMap::new());
+                open_project(&project, library.as_deref(), &args, &xdg_)
+                    .map_err(mlua::Error::external)
+            },
+        )?,
+    )?;
+
+    let args = context.args.clone();
+    module.set(
+        "prompt",
+        lua.create_function(
+            move |_, (text, default, name): (String, Option<String>, Option<String>)| {
+                prompts::prompt(&text, default.as_deref(), name.as_deref(), &args)
+                    .map_err(mlua::Error::external)
+            },
+        )?,
+    )?;
+
+    let args = context.args.clone();
+    module.set(
+        "confirm",
+        lua.create_function(
+            move |_, (text, default, name): (String, Option<bool>, Option<String>)| {
+                prompts::confirm(&text, default, name.as_deref(), &args)
+                    .map_err(mlua::Error::external)
+            },
+        )?,
+    )?;
+
+    let args = context.args.clone();
+    module.set(
+        "select",
+        lua.create_function(
+            move |_,
+                  (text, options, default, name): (
+                String,
+                Vec<String>,
+                Option<String>,
+                Option<String>,
+            )| {
+                prompts::select(&text, &options, default.as_deref(), name.as_deref(), &args)
+                    .map_err(mlua::Error::external)
+            },
+        )?,
+    )?;
+
+    let dir = context.working_dir().to_path_buf();
+    module.set(
+        "exec",
+        lua.create_function(move |lua, options: Table| {
+            let mut command = table_to_command("exec", &options, &dir)?;
+            let capture: Option<bool> = options.get("capture")?;
+            let result = lua.create_table()?;
+            let status = match capture.unwrap_or(true) {
+                true => {
+                    let output = command
+                        .stdin(Stdio::null())
+                        .output()
+                        .map_err(|e| command_error(&command, e))?;
+                    result.set("stdout", lua.create_string(&output.stdout)?)?;
+                    result.set("stderr", lua.create_string(&output.stderr)?)?;
+                    output.status
+                }
+                false => command.status().map_err(|e| command_error(&command, e))?,
+            };
+            result.set("code", status.code())?;
+            Ok(result)
+        })?,
+    )?;
+
+    let dir = context.working_dir().to_path_buf();
+    module.set(
+        "spawn",
+        lua.create_function(move |_, options: Table| {
+            let mut command = table_to_command("spawn", &options, &dir)?;
+            command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            // a process group of its own keeps it running when the terminal donna was run from is closed
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+            let child = command.spawn().map_err(|e| command_error(&command, e))?;
+            Ok(child.id())
+        })?,
+    )?;
+
+    Ok(module)
+}
@@ -3,18 +3,29 @@ use std::io::Write;
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Shell, ValueHint};
 use donna::{
-    create_alias_group, create_lib, create_project, define_project_type, env_setup,
+    add_to_alias_group, archive_project, create_alias_group, create_lib, create_profile,
+    create_project, define_project_type, delete_library, delete_project, doctor,
     errors::{
-        ConfigError, CreateAliasGroupError, CreateLibError, CreateProjectError,
+        AddToAliasGroupError, ArchiveProjectError, ConfigError, ConfigValueError,
+        CreateAliasGroupError, CreateLibError, CreateProfileError, CreateProjectError,
+        DeleteLibraryError, DeleteProjectError, DoctorError, ExecutableError, ExportError,
         GetAliasGroupsError, GetLibsError, GetProjectPathError, GetProjectTypesError,
-        GetProjectsError, OpenBuildersError, OpenConfigError, OpenOpenersError, OpenProjectError,
-        ProjectTypeDefinitionError, UntrackAliasGroupError, UntrackLibError,
-        UntrackProjectTypeError,
-    },
-    get_alias_groups, get_builders_path, get_config_path, get_libraries, get_openers_path,
-    get_project_path, get_project_types, get_projects, open_builders, open_config, open_openers,
-    open_project, set_builders_path_prefix, set_default_lib, set_openers_path_prefix,
-    untrack_alias_group, untrack_library, untrack_project_type, utils, ProjectConfig,
+        GetProjectsError, InitError, MigrateError, OpenBuildersError, OpenConfigError,
+        OpenOpenersError, OpenProjectError, ProjectTypeDefinitionError, RepairAliasLinksError,
+        ResolveProfileError, RestoreError, SwitchProfileError, UntrackAliasGroupError,
+        UntrackLibError, UntrackProjectTypeError, UpdateAliasGroupError, UpdateLibraryError,
+        UpdateProjectError, ValidateConfigError,
+    },
+    export_state, format_config_key, get_alias_groups, get_builders_path, get_config_keys,
+    get_config_path, get_config_value, get_config_values, get_libraries, get_library_aliases,
+    get_library_defaults, get_library_dirs, get_offline_libraries, get_openers_path, get_profiles,
+    get_project_path, get_project_types, get_projects, init, migrate, open_builders, open_config,
+    open_openers, open_project, repair_alias_links, resolve_profile, restore_state,
+    set_builders_path_prefix, set_config_value, set_default_lib, set_ignore_patterns,
+    set_library_defaults, set_openers_path_prefix, set_relative_links, switch_profile,
+    unset_config_value, untrack_alias_group, untrack_library, untrack_project_type,
+    update_alias_group, update_library, update_project, utils, validate_config, Builder,
+    LibraryDefaults, ProjectConfig, ProjectRestoreStatus, ScriptArgs, XDG,
 };
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -33,6 +44,10 @@ struct Cli {
     #[arg(long, value_enum, default_value_t = Verbosity::Info)]
     verbose: Verbosity,
 
+    /// Profile to use, defaults to DONNA_PROFILE or the profile set with `donna profile switch`
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -69,15 +84,44 @@ enum Commands {
         #[arg(short = 'n', long, default_value_t = true)]
         new: bool,
 
-        /// Default type of all projects unless specified otherwise
+        /// Default type of all projects unless specified otherwise, saved as the library default
         #[arg(short = 't', long)]
         project_type: Option<String>,
 
+        /// Alias groups every project created in or imported into the library is added to
+        #[arg(short = 'g', long, num_args(0..))]
+        alias_groups: Option<Vec<String>>,
+
+        /// Builder for projects in the library, overrides the project type builder, relative to `builders_dir`.
+        /// Several scripts are run in order as steps
+        #[arg(short, long, num_args(1..), value_hint = ValueHint::ExecutablePath)]
+        builder: Option<Vec<String>>,
+
+        /// Opener for projects in the library, overrides the project type opener, relative to `openers_dir`
+        #[arg(short, long, value_hint = ValueHint::ExecutablePath)]
+        opener: Option<String>,
+
         /// Don't ask for confirmation
         #[arg(short = 'y', long)]
         yes: bool,
     },
 
+    /// Rename or move a library, alias group or project
+    Update {
+        #[command(subcommand)]
+        entity: UpdateEntity,
+    },
+
+    /// Move a project to the archive library set with `archive_lib`
+    Archive {
+        /// Name of the project
+        name: String,
+
+        /// Library of the project
+        #[arg(short = 'l', long)]
+        lib: Option<String>,
+    },
+
     /// Set configuration options
     Set {
         #[command(subcommand)]
@@ -89,20 +133,92 @@ enum Commands {
         entity: OpenEntity,
     },
 
-    /// Delete a project, library, alias group, project type, not implemented yet
-    Delete, // /// List all projects
-    // List,
-    // /// Open a project
-    // Open {
-    //     /// Name of the project
-    //     name: String,
-    // },
+    /// Delete a library or project, moves it to the system trash
+    Delete {
+        #[command(subcommand)]
+        entity: DeleteEntity,
+    },
+
     /// Forget about an alias group, library, or project type, donna will no longer track it
     Forget {
         #[command(subcommand)]
         entity: ForgetEntity,
     },
 
+    /// Check tracked alias groups for broken aliases and aliases that don't match the configured link style
+    Doctor,
+
+    /// List, create or switch between profiles, each profile has its own libraries, alias groups and project types
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
+    /// Inspect the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Upgrade the config and project configs written by older versions of donna, originals are backed up
+    Migrate {
+        /// Only list the files that need migrating, without changing them
+        #[arg(long, default_value_t = false)]
+        check: bool,
+    },
+
+    /// Export the config, builders, openers and projects to a file that `donna restore` can set up another machine from
+    Export {
+        /// File to write the export to
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: String,
+    },
+
+    /// Set donna up from a file written by `donna export`, projects with a git remote are cloned
+    Restore {
+        /// File written by `donna export`
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: String,
+
+        /// Replace the current config even if it already tracks libraries, alias groups or project types
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+
+    /// Rewrite existing aliases as relative or absolute symlinks, defaults to the configured style
+    Repair {
+        /// Only repair this alias group
+        #[arg(short = 'g', long)]
+        alias_group: Option<String>,
+
+        /// Convert aliases to relative symlinks
+        #[arg(long, conflicts_with = "absolute")]
+        relative: bool,
+
+        /// Convert aliases to absolute symlinks
+        #[arg(long)]
+        absolute: bool,
+    },
+
+    /// Set donna up: create the config and data directories, register existing folders and install example scripts
+    Init {
+        /// Don't ask anything, only register the folders given as arguments
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// Register a folder as a library, as name=path
+        #[arg(short = 'l', long = "library", value_parser = parse_name_path)]
+        libraries: Vec<(String, String)>,
+
+        /// Register a folder as an alias group, as name=path
+        #[arg(short = 'g', long = "alias-group", value_parser = parse_name_path)]
+        alias_groups: Vec<(String, String)>,
+
+        /// Don't install the example builder and opener
+        #[arg(long, default_value_t = false)]
+        no_examples: bool,
+    },
+
     /// Generate shell completion scripts
     Completion {
         #[arg(value_enum)]
@@ -148,6 +264,33 @@ enum CreateEntity {
         /// Url of git repository to clone, this overides the builder and conficts with handoff
         #[arg(short = 'u', long, value_hint = ValueHint::Url)]
         git_clone: Option<String>,
+
+        /// Answer a question the builder asks, as name=value
+        #[arg(short = 'p', long = "param", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+
+        /// Don't ask the builder's questions, confirm them and use their defaults
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// Arguments passed to the builder, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+
+    /// Link an existing project into an alias group
+    Alias {
+        /// Name of the project
+        #[arg(value_hint = ValueHint::Other)]
+        project: String,
+
+        /// Alias group to link the project into
+        #[arg(short = 'g', long)]
+        alias_group: String,
+
+        /// Library of the project
+        #[arg(short = 'l', long)]
+        library: Option<String>,
     },
 
     /// Create a new alias group
@@ -163,6 +306,10 @@ enum CreateEntity {
         /// Whether to create a new directory for the group or handoff an existing one to the pm
         #[arg(short = 'H', long, default_value_t = false)]
         handoff: bool,
+
+        /// Create aliases in this group as relative symlinks, overrides the config setting
+        #[arg(short = 'r', long, default_value_t = false)]
+        relative: bool,
     },
 
     /// Create a new library
@@ -182,6 +329,23 @@ enum CreateEntity {
         /// Whether to create a new directory for the project or handoff an existing one to the pm
         #[arg(short = 'H', long, default_value_t = false)]
         handoff: bool,
+
+        /// Default type of projects created in or imported into the library
+        #[arg(short = 't', long)]
+        project_type: Option<String>,
+
+        /// Alias groups every project created in or imported into the library is added to
+        #[arg(short = 'g', long, num_args(0..))]
+        alias_groups: Option<Vec<String>>,
+
+        /// Builder for projects in the library, overrides the project type builder, relative to `builders_dir`.
+        /// Several scripts are run in order as steps
+        #[arg(short, long, num_args(1..), value_hint = ValueHint::ExecutablePath)]
+        builder: Option<Vec<String>>,
+
+        /// Opener for projects in the library, overrides the project type opener, relative to `openers_dir`
+        #[arg(short, long, value_hint = ValueHint::ExecutablePath)]
+        opener: Option<String>,
     },
 
     /// Create a new project type
@@ -196,9 +360,9 @@ enum CreateEntity {
         /// Path to the opener for the project type, this will be relative to the config variable `openers_dir`, the default is in the share directory   
         #[arg(short, long, value_hint = ValueHint::ExecutablePath)]
         opener: Option<String>,
-        /// Path to the builder for the project type, this will be relative to the config variable `builders_dir`, the default is in the share directory
-        #[arg(short, long, value_hint = ValueHint::ExecutablePath)]
-        builder: Option<String>,
+        /// Path to the builder for the project type, this will be relative to the config variable `builders_dir`, the default is in the share directory. Several scripts are run in order as steps
+        #[arg(short, long, num_args(1..), value_hint = ValueHint::ExecutablePath)]
+        builder: Option<Vec<String>>,
 
         #[arg(short, long, default_value_t = false)]
         redefine: bool,
@@ -237,6 +401,56 @@ enum ListEntity {
     ProjectTypes {},
 }
 
+#[derive(Subcommand, Debug)]
+#[command(version, about, long_about = None)]
+enum UpdateEntity {
+    /// Rename or move a library, aliases pointing into it are updated
+    Library {
+        /// Name of the library
+        name: String,
+
+        /// New name for the library
+        #[arg(short = 'n', long)]
+        new_name: Option<String>,
+
+        /// New path for the library, must not exist yet
+        #[arg(short = 'p', long, value_hint = ValueHint::DirPath)]
+        new_path: Option<String>,
+    },
+
+    /// Rename a project or move it to another library, aliases pointing into it are updated
+    Project {
+        /// Name of the project
+        name: String,
+
+        /// Library of the project
+        #[arg(short = 'l', long)]
+        lib: Option<String>,
+
+        /// New name for the project
+        #[arg(short = 'n', long)]
+        new_name: Option<String>,
+
+        /// Library to move the project to
+        #[arg(long)]
+        new_lib: Option<String>,
+    },
+
+    /// Rename or move an alias group
+    AliasGroup {
+        /// Name of the alias group
+        name: String,
+
+        /// New name for the alias group
+        #[arg(short = 'n', long)]
+        new_name: Option<String>,
+
+        /// New path for the alias group
+        #[arg(short = 'p', long, value_hint = ValueHint::DirPath)]
+        new_path: Option<String>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 #[command(version, about, long_about = None)]
 enum SetOption {
@@ -259,6 +473,131 @@ enum SetOption {
         #[arg(value_hint = ValueHint::DirPath)]
         path: String,
     },
+
+    /// Defaults for projects created in or imported into a library, replaces the current defaults
+    LibraryDefaults {
+        /// Name of the library
+        name: String,
+
+        /// Default project type
+        #[arg(short = 't', long)]
+        project_type: Option<String>,
+
+        /// Alias groups every project is added to
+        #[arg(short = 'g', long, num_args(0..))]
+        alias_groups: Option<Vec<String>>,
+
+        /// Builder override, relative to `builders_dir`. Several scripts are run in order as steps
+        #[arg(short, long, num_args(1..), value_hint = ValueHint::ExecutablePath)]
+        builder: Option<Vec<String>>,
+
+        /// Opener override, relative to `openers_dir`
+        #[arg(short, long, value_hint = ValueHint::ExecutablePath)]
+        opener: Option<String>,
+    },
+
+    /// Patterns of directories to skip when scanning libraries, matched against the directory name
+    IgnorePatterns {
+        /// Glob patterns, e.g. `scratch*`, leave empty to remove all patterns
+        patterns: Vec<String>,
+    },
+
+    /// Whether new aliases are created as relative symlinks
+    RelativeLinks {
+        /// Use relative symlinks
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+
+        /// Only set the option for this alias group
+        #[arg(short = 'g', long)]
+        alias_group: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[command(version, about, long_about = None)]
+enum ProfileAction {
+    /// List all profiles, the active one is marked with *
+    List,
+
+    /// Create a new profile
+    Create {
+        /// Name of the profile
+        name: String,
+
+        /// Give the profile its own builders and openers directories instead of sharing them
+        #[arg(long, default_value_t = false)]
+        separate_scripts: bool,
+    },
+
+    /// Set the profile used when --profile and DONNA_PROFILE aren't given
+    Switch {
+        /// Name of the profile, "default" for the default config
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[command(version, about, long_about = None)]
+enum ConfigAction {
+    /// Print the value of a config key, e.g. `project_types.rust.builder`
+    Get {
+        /// Dotted path of the key
+        key: String,
+    },
+
+    /// Set a config key, lists take any number of values
+    Set {
+        /// Dotted path of the key
+        key: String,
+
+        /// The value, or the items of a list
+        #[arg(required = true, num_args(1..))]
+        values: Vec<String>,
+    },
+
+    /// Remove a config key so its default is used
+    Unset {
+        /// Dotted path of the key
+        key: String,
+    },
+    /// Show the config after the shared config files are merged into it
+    Show {
+        /// Show which file each value was set in
+        #[arg(long, default_value_t = false)]
+        origin: bool,
+    },
+
+    /// Check the config file for syntax errors, unknown keys and references to things that don't exist
+    Validate,
+}
+
+#[derive(Subcommand, Debug)]
+#[command(version, about, long_about = None)]
+enum DeleteEntity {
+    /// Delete a library, all projects in it and all aliases pointing into it
+    Library {
+        /// Name of the library
+        name: String,
+
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Delete a project and all aliases pointing into it
+    Project {
+        /// Name of the project
+        name: String,
+
+        /// Library of the project
+        #[arg(short = 'l', long)]
+        lib: Option<String>,
+
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -298,6 +637,18 @@ enum OpenEntity {
         /// Library to open the project with
         #[arg(short = 't', long, default_value_t = false)]
         terminal: bool,
+
+        /// Answer a question the opener asks, as name=value
+        #[arg(short = 'p', long = "param", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+
+        /// Don't ask the opener's questions, confirm them and use their defaults
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// Arguments passed to the opener, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
     },
 
     /// Open the config file
@@ -322,18 +673,90 @@ enum OpenEntity {
     },
 }
 
-fn handle_config_error(error: ConfigError) {
+fn parse_name_path(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), path.to_string()))
+        }
+        _ => Err(format!("expected name=path, got {value}")),
+    }
+}
+
+fn parse_param(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected name=value, got {value}")),
+    }
+}
+
+/// Ask for name=path pairs until an empty line is entered
+fn prompt_name_paths(prompt: &str) -> Vec<(String, String)> {
+    println!("{prompt} Enter name=path, or an empty line to continue.");
+    let mut pairs = Vec::new();
+    loop {
+        print!("> ");
+        std::io::stdout().flush().unwrap();
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 || input.trim().is_empty() {
+            return pairs;
+        }
+        match parse_name_path(input.trim()) {
+            Ok(pair) => pairs.push(pair),
+            Err(err) => println!("{err}"),
+        }
+    }
+}
+
+/// Print the error of a builder or opener script with its traceback and exit
+fn handle_script_error(kind: &str, path: &str, message: &str, traceback: Option<&str>) -> ! {
+    println!("{kind} {path} failed:\n{message}");
+    if let Some(traceback) = traceback {
+        println!("{traceback}");
+    }
+    std::process::exit(1);
+}
+
+/// Print the error of an executable builder, opener or hook and exit with its exit code
+fn handle_executable_error(kind: &str, error: &ExecutableError) -> ! {
+    println!("{kind} {error}");
+    std::process::exit(error.code.unwrap_or(1));
+}
+
+/// Print a config error and exit with status 1
+fn handle_config_error(error: ConfigError) -> ! {
     match error {
-        ConfigError::BadPath(_) => {
-            println!("Config file not found. Does it exist under ~/.config/donna/config.toml?");
+        ConfigError::BadPath(error) => {
+            println!("Error reading config file: {error}");
         }
-        ConfigError::TomlLoad(_) => {
-            println!("Error parsing config file.");
+        ConfigError::TomlLoad(path, error) => {
+            println!("Error parsing config file {}:\n{error}", path.display());
         }
         ConfigError::TomlSave(_) => {
             println!("Error saving config file.");
         }
+        ConfigError::UnsupportedVersion(version) => {
+            println!("Config file version {version} is newer than this version of donna supports, please upgrade donna.");
+        }
+        ConfigError::InvalidVersion(error) => {
+            println!("Config file has an invalid version. {error}");
+        }
+        ConfigError::PathVarNotDefined(error) => {
+            println!("Error expanding a path in the config file. {error}");
+        }
+        ConfigError::NotInitialized(path) => {
+            println!(
+                "Donna is not set up yet, {} does not exist. Run `donna init` to set it up.",
+                path.display()
+            );
+        }
+        ConfigError::InheritedKey(key, path) => {
+            println!(
+                "{key} is set in the shared config file {}, it can only be removed there.",
+                path.display()
+            );
+        }
     }
+    std::process::exit(1);
 }
 
 fn main() {
@@ -355,9 +778,73 @@ fn main() {
     env_logger::init();
 
     let xdg = donna::XDG::new(None, None, None);
-    env_setup::setup_pm(&xdg);
+    let xdg = match resolve_profile(args.profile.as_deref(), &xdg) {
+        Ok(profile) => xdg.with_profile(profile.as_deref()),
+        Err(ResolveProfileError::ConfigError(config_error)) => {
+            handle_config_error(config_error);
+        }
+        Err(err) => {
+            println!("Error selecting profile: {err}");
+            std::process::exit(1);
+        }
+    };
 
     match &args.command {
+        Commands::Init {
+            yes,
+            libraries,
+            alias_groups,
+            no_examples,
+        } => {
+            let mut libraries = libraries.clone();
+            let mut alias_groups = alias_groups.clone();
+            let mut examples = !*no_examples;
+            if !*yes {
+                println!(
+                    "Setting donna up, the config is at {}",
+                    get_config_path(&xdg).display()
+                );
+                if libraries.is_empty() {
+                    libraries = prompt_name_paths(
+                        "Libraries hold your projects, register existing folders as libraries?",
+                    );
+                }
+                if alias_groups.is_empty() {
+                    alias_groups = prompt_name_paths(
+                        "Alias groups link projects into other folders, register existing folders as alias groups?",
+                    );
+                }
+                if examples {
+                    print!("Install an example builder and opener? [Y/n] ");
+                    std::io::stdout().flush().unwrap();
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input).unwrap();
+                    let input = input.trim().to_lowercase();
+                    examples = input.is_empty() || input == "y" || input == "yes";
+                }
+            }
+            let libraries: Vec<(&str, &str)> = libraries
+                .iter()
+                .map(|(name, path)| (name.as_str(), path.as_str()))
+                .collect();
+            let alias_groups: Vec<(&str, &str)> = alias_groups
+                .iter()
+                .map(|(name, path)| (name.as_str(), path.as_str()))
+                .collect();
+            match init(&libraries, &alias_groups, examples, &xdg) {
+                Ok(()) => {
+                    println!("Donna is set up, edit the config with `donna open config` or `donna config set`.");
+                }
+                Err(InitError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => {
+                    println!("Error setting donna up: {err}");
+                    std::process::exit(1);
+                }
+            }
+        }
+
         Commands::Completion { shell } => {
             let mut app = Cli::command();
             let mut buf = Vec::new();
@@ -399,6 +886,16 @@ fn main() {
                     println!("{name}");
                 }
             }
+            "config-keys" => {
+                for key in get_config_keys(&xdg).unwrap_or_default() {
+                    println!("{key}");
+                }
+            }
+            "profiles" => {
+                for name in get_profiles(&xdg).unwrap_or_default() {
+                    println!("{name}");
+                }
+            }
             "projects" => {
                 let projects = get_projects(&xdg).unwrap_or_default();
                 for (name, (_, project_lib, _)) in projects {
@@ -421,6 +918,9 @@ fn main() {
                 alias_groups,
                 library,
                 git_clone,
+                params,
+                yes,
+                args,
             } => {
                 match create_project(
                     name,
@@ -432,6 +932,9 @@ fn main() {
                     library.as_deref(),
                     *handoff,
                     git_clone.as_deref(),
+                    &ScriptArgs::new(args.clone())
+                        .with_params(params.clone())
+                        .with_assume_yes(*yes),
                     &xdg,
                 ) {
                     Ok(_) => {
@@ -440,15 +943,51 @@ fn main() {
                     Err(CreateProjectError::ConfigError(config_error)) => {
                         handle_config_error(config_error);
                     }
+                    Err(CreateProjectError::BuilderRuntime(err)) => {
+                        handle_script_error(
+                            "Builder",
+                            &err.path,
+                            &err.message,
+                            err.traceback.as_deref(),
+                        );
+                    }
+                    Err(CreateProjectError::Executable(err)) => {
+                        handle_executable_error("Builder", &err);
+                    }
                     Err(err) => {
                         println!("Error creating project: {err}");
                     }
                 };
             }
+            CreateEntity::Alias {
+                project,
+                alias_group,
+                library,
+            } => match add_to_alias_group(project, alias_group, library.as_deref(), &xdg) {
+                Ok(()) => {}
+                Err(AddToAliasGroupError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(AddToAliasGroupError::HookRuntime(err)) => {
+                    handle_script_error(
+                        &format!("Hook {}", err.hook),
+                        &err.path,
+                        &err.message,
+                        err.traceback.as_deref(),
+                    );
+                }
+                Err(AddToAliasGroupError::Executable(err)) => {
+                    handle_executable_error("Hook", &err);
+                }
+                Err(err) => {
+                    println!("Error adding project to alias group: {err}");
+                }
+            },
             CreateEntity::AliasGroup {
                 name,
                 handoff,
                 path,
+                relative,
             } => {
                 match create_alias_group(name, path.as_str(), *handoff, &xdg) {
                     Ok(_) => {}
@@ -457,26 +996,43 @@ fn main() {
                     }
                     Err(err) => {
                         println!("Error creating alias group: {err}");
+                        return;
                     }
                 };
+                if *relative {
+                    if let Err(err) = set_relative_links(Some(true), Some(name), &xdg) {
+                        println!("Error setting relative links: {err}");
+                    }
+                }
             }
             CreateEntity::Lib {
                 name,
                 path,
                 default,
                 handoff,
+                project_type,
+                alias_groups,
+                builder,
+                opener,
             } => {
-                match create_lib(name, path, *default, *handoff, &xdg) {
-                    Ok(_) => {
-                        println!("Library '{name}' created successfully.");
-                    }
+                let defaults = LibraryDefaults {
+                    project_type: project_type.clone(),
+                    default_alias_groups: alias_groups.clone(),
+                    builder: builder.clone().map(Builder::new),
+                    opener: opener.clone(),
+                    ..Default::default()
+                };
+                match create_lib(name, path, *default, *handoff, Some(&defaults), &xdg) {
+                    Ok(_) => {}
                     Err(CreateLibError::ConfigError(config_error)) => {
                         handle_config_error(config_error);
                     }
                     Err(err) => {
                         println!("Error creating library: {err}");
+                        return;
                     }
                 };
+                println!("Library '{name}' created successfully.");
             }
             CreateEntity::ProjectType {
                 name,
@@ -488,7 +1044,7 @@ fn main() {
                 match define_project_type(
                     name,
                     default_groups.clone(),
-                    builder.as_deref(),
+                    builder.clone(),
                     opener.as_deref(),
                     *redefine,
                     &xdg,
@@ -517,7 +1073,6 @@ fn main() {
                     Ok(projects) => projects,
                     Err(GetProjectsError::ConfigError(config_error)) => {
                         handle_config_error(config_error);
-                        return;
                     }
                     Err(err) => {
                         println!("Error getting projects: {err}");
@@ -591,27 +1146,49 @@ fn main() {
                     Ok(libs) => libs,
                     Err(GetLibsError::ConfigError(err)) => {
                         handle_config_error(err);
-                        return;
                     }
                     Err(err) => {
                         println!("Error getting libraries: {err}");
                         return;
                     }
                 };
+                let lib_defaults = get_library_defaults(&xdg).unwrap_or_default();
+                let offline_libs = get_offline_libraries(&xdg).unwrap_or_default();
                 let rows: Vec<Vec<String>> = libs
                     .iter()
-                    .map(|(name, path)| vec![name.clone(), path.clone()])
+                    .map(|(name, path)| {
+                        let defaults = lib_defaults.get(name).cloned().unwrap_or_default();
+                        vec![
+                            name.clone(),
+                            path.clone(),
+                            if offline_libs.contains(name) {
+                                "unavailable".to_string()
+                            } else {
+                                "available".to_string()
+                            },
+                            defaults.project_type.unwrap_or_default(),
+                            defaults
+                                .default_alias_groups
+                                .map(|v| v.join(", "))
+                                .unwrap_or_default(),
+                        ]
+                    })
                     .collect();
-                let headers = vec!["Name".to_string(), "Path".to_string()];
-                utils::pretty_print_table(rows, headers);
-            }
+                let headers = vec![
+                    "Name".to_string(),
+                    "Path".to_string(),
+                    "Status".to_string(),
+                    "Default Type".to_string(),
+                    "Default Groups".to_string(),
+                ];
+                utils::pretty_print_table(rows, headers);
+            }
 
             ListEntity::AliasGroups {} => {
                 let alias_groups = match get_alias_groups(&xdg) {
                     Ok(groups) => groups,
                     Err(GetAliasGroupsError::ConfigError(err)) => {
                         handle_config_error(err);
-                        return;
                     }
                     Err(err) => {
                         println!("Error getting alias groups: {err}");
@@ -631,7 +1208,6 @@ fn main() {
                     Ok(types) => types,
                     Err(GetProjectTypesError::ConfigError(err)) => {
                         handle_config_error(err);
-                        return;
                     }
                 };
                 let rows: Vec<Vec<String>> = project_types
@@ -639,7 +1215,10 @@ fn main() {
                     .map(|(name, pt)| {
                         vec![
                             name.clone(),
-                            pt.builder.clone().unwrap_or("".to_string()),
+                            pt.builder
+                                .as_ref()
+                                .map(ToString::to_string)
+                                .unwrap_or("".to_string()),
                             pt.opener.clone().unwrap_or("".to_string()),
                             pt.default_alias_groups
                                 .clone()
@@ -664,27 +1243,38 @@ fn main() {
             default,
             new,
             project_type,
+            alias_groups,
+            builder,
+            opener,
             yes,
         } => {
-            match create_lib(name, path, *default, true, &xdg) {
+            let defaults = LibraryDefaults {
+                project_type: project_type.clone(),
+                default_alias_groups: alias_groups.clone(),
+                builder: builder.clone().map(Builder::new),
+                opener: opener.clone(),
+                ..Default::default()
+            };
+            match create_lib(name, path, *default, true, Some(&defaults), &xdg) {
                 Ok(_) => {
                     println!("Library '{name}' created successfully.");
                 }
                 Err(CreateLibError::ConfigError(config_error)) => {
                     handle_config_error(config_error);
-                    return;
                 }
                 Err(err) => {
                     println!("Error creating library: {err}");
                     return;
                 }
             };
-            let dir_items = std::fs::read_dir(path).unwrap();
-            for item in dir_items.flatten() {
-                let path = item.path();
-                if !path.is_dir() {
-                    continue;
+            let dirs = match get_library_dirs(name, &xdg) {
+                Ok(dirs) => dirs,
+                Err(err) => {
+                    println!("Error reading library: {err}");
+                    return;
                 }
+            };
+            for path in dirs {
                 let project_name = path.file_name().unwrap().to_str().unwrap();
 
                 if *new
@@ -729,6 +1319,7 @@ fn main() {
                     Some(name),
                     true,
                     None,
+                    &ScriptArgs::default(),
                     &xdg,
                 ) {
                     Ok(_) => {
@@ -736,7 +1327,6 @@ fn main() {
                     }
                     Err(CreateProjectError::ConfigError(config_error)) => {
                         handle_config_error(config_error);
-                        return;
                     }
                     Err(err) => {
                         println!("Error creating project: {err}");
@@ -746,6 +1336,106 @@ fn main() {
             }
         }
 
+        Commands::Update { entity } => match entity {
+            UpdateEntity::Library {
+                name,
+                new_name,
+                new_path,
+            } => match update_library(name, new_name.as_deref(), new_path.as_deref(), &xdg) {
+                Ok(_) => {
+                    println!("Library '{name}' updated successfully.");
+                }
+                Err(UpdateLibraryError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(UpdateLibraryError::HookRuntime(err)) => {
+                    handle_script_error(
+                        &format!("Hook {}", err.hook),
+                        &err.path,
+                        &err.message,
+                        err.traceback.as_deref(),
+                    );
+                }
+                Err(UpdateLibraryError::Executable(err)) => {
+                    handle_executable_error("Hook", &err);
+                }
+                Err(err) => {
+                    println!("Error updating library: {err}");
+                }
+            },
+            UpdateEntity::Project {
+                name,
+                lib,
+                new_name,
+                new_lib,
+            } => match update_project(
+                name,
+                lib.as_deref(),
+                new_name.as_deref(),
+                new_lib.as_deref(),
+                &xdg,
+            ) {
+                Ok(_) => {
+                    println!("Project '{name}' updated successfully.");
+                }
+                Err(UpdateProjectError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(UpdateProjectError::HookRuntime(err)) => {
+                    handle_script_error(
+                        &format!("Hook {}", err.hook),
+                        &err.path,
+                        &err.message,
+                        err.traceback.as_deref(),
+                    );
+                }
+                Err(UpdateProjectError::Executable(err)) => {
+                    handle_executable_error("Hook", &err);
+                }
+                Err(err) => {
+                    println!("Error updating project: {err}");
+                }
+            },
+            UpdateEntity::AliasGroup {
+                name,
+                new_name,
+                new_path,
+            } => match update_alias_group(name, new_name.as_deref(), new_path.as_deref(), &xdg) {
+                Ok(_) => {
+                    println!("Alias group '{name}' updated successfully.");
+                }
+                Err(UpdateAliasGroupError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => {
+                    println!("Error updating alias group: {err}");
+                }
+            },
+        },
+
+        Commands::Archive { name, lib } => match archive_project(name, lib.as_deref(), &xdg) {
+            Ok(_) => {
+                println!("Project '{name}' archived successfully.");
+            }
+            Err(ArchiveProjectError::ConfigError(config_error)) => {
+                handle_config_error(config_error);
+            }
+            Err(ArchiveProjectError::HookRuntime(err)) => {
+                handle_script_error(
+                    &format!("Hook {}", err.hook),
+                    &err.path,
+                    &err.message,
+                    err.traceback.as_deref(),
+                );
+            }
+            Err(ArchiveProjectError::Executable(err)) => {
+                handle_executable_error("Hook", &err);
+            }
+            Err(err) => {
+                println!("Error archiving project: {err}");
+            }
+        },
+
         Commands::Set { option } => match option {
             SetOption::DefaultLib { name } => {
                 match set_default_lib(name, &xdg) {
@@ -777,20 +1467,275 @@ fn main() {
                     }
                 };
             }
+            SetOption::LibraryDefaults {
+                name,
+                project_type,
+                alias_groups,
+                builder,
+                opener,
+            } => {
+                match set_library_defaults(
+                    name,
+                    project_type.as_deref(),
+                    alias_groups.clone(),
+                    builder.clone(),
+                    opener.as_deref(),
+                    &xdg,
+                ) {
+                    Ok(_) => {
+                        println!("Defaults for library '{name}' set");
+                    }
+                    Err(err) => {
+                        println!("Error setting library defaults: {err}");
+                    }
+                };
+            }
+            SetOption::IgnorePatterns { patterns } => {
+                let patterns = (!patterns.is_empty()).then(|| patterns.clone());
+                match set_ignore_patterns(patterns, &xdg) {
+                    Ok(_) => {
+                        println!("Ignore patterns set");
+                    }
+                    Err(err) => {
+                        println!("Error setting ignore patterns: {err}");
+                    }
+                };
+            }
+            SetOption::RelativeLinks {
+                enabled,
+                alias_group,
+            } => {
+                match set_relative_links(Some(*enabled), alias_group.as_deref(), &xdg) {
+                    Ok(_) => {
+                        println!("Relative links set to '{enabled}'");
+                    }
+                    Err(err) => {
+                        println!("Error setting relative links: {err}");
+                    }
+                };
+            }
+        },
+
+        Commands::Doctor => {
+            let issues = match doctor(&xdg) {
+                Ok(issues) => issues,
+                Err(DoctorError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => {
+                    println!("Error checking health: {err}");
+                    return;
+                }
+            };
+            if issues.is_empty() {
+                println!("No issues found.");
+            }
+            for issue in issues {
+                println!("{issue}");
+            }
+        }
+
+        Commands::Profile { action } => match action {
+            ProfileAction::List => {
+                let profiles = match get_profiles(&xdg) {
+                    Ok(profiles) => profiles,
+                    Err(err) => {
+                        println!("Error listing profiles: {err}");
+                        return;
+                    }
+                };
+                let active = xdg.profile.as_deref().unwrap_or(XDG::DEFAULT_PROFILE);
+                for name in profiles {
+                    let marker = if name == active { "*" } else { " " };
+                    println!("{marker} {name}");
+                }
+            }
+            ProfileAction::Create {
+                name,
+                separate_scripts,
+            } => match create_profile(name, *separate_scripts, &xdg) {
+                Ok(_) => println!("Profile '{name}' created successfully."),
+                Err(CreateProfileError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => println!("Error creating profile: {err}"),
+            },
+            ProfileAction::Switch { name } => match switch_profile(name, &xdg) {
+                Ok(_) => println!("Switched to profile '{name}'."),
+                Err(SwitchProfileError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => println!("Error switching profile: {err}"),
+            },
+        },
+
+        Commands::Config { action } => match action {
+            ConfigAction::Get { key } => match get_config_value(key, &xdg) {
+                Ok(Some(toml::Value::String(value))) => println!("{value}"),
+                Ok(Some(value)) => println!("{value}"),
+                Ok(None) => println!("{key} is not set."),
+                Err(ConfigValueError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => println!("Error getting config value: {err}"),
+            },
+            ConfigAction::Set { key, values } => {
+                let values: Vec<&str> = values.iter().map(String::as_str).collect();
+                match set_config_value(key, &values, &xdg) {
+                    Ok(_) => println!("Set {key}."),
+                    Err(ConfigValueError::ConfigError(config_error)) => {
+                        handle_config_error(config_error);
+                    }
+                    Err(err) => println!("Error setting config value: {err}"),
+                }
+            }
+            ConfigAction::Unset { key } => match unset_config_value(key, &xdg) {
+                Ok(_) => println!("Unset {key}."),
+                Err(ConfigValueError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => println!("Error unsetting config value: {err}"),
+            },
+            ConfigAction::Show { origin } => {
+                let values = match get_config_values(&xdg) {
+                    Ok(values) => values,
+                    Err(config_error) => {
+                        handle_config_error(config_error);
+                    }
+                };
+                for value in values {
+                    let key = format_config_key(&value.key);
+                    match (origin, &value.origin) {
+                        (false, _) => println!("{key} = {}", value.value),
+                        (true, Some(file)) => {
+                            println!("{key} = {}  # {}", value.value, file.display())
+                        }
+                        (true, None) => println!("{key} = {}  # default", value.value),
+                    }
+                }
+            }
+            ConfigAction::Validate => {
+                let issues = match validate_config(&xdg) {
+                    Ok(issues) => issues,
+                    Err(ValidateConfigError::ConfigError(config_error)) => {
+                        handle_config_error(config_error);
+                    }
+                };
+                let path = get_config_path(&xdg);
+                if issues.is_empty() {
+                    println!("{} is valid.", path.display());
+                    return;
+                }
+                for issue in &issues {
+                    match issue.location {
+                        Some((line, column)) => {
+                            println!("{}:{line}:{column}: {issue}", issue.file.display())
+                        }
+                        None => println!("{}: {issue}", issue.file.display()),
+                    }
+                }
+                std::process::exit(1);
+            }
+        },
+
+        Commands::Migrate { check } => {
+            let migrated = match migrate(*check, &xdg) {
+                Ok(migrated) => migrated,
+                Err(MigrateError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => {
+                    println!("Error migrating: {err}");
+                    return;
+                }
+            };
+            if migrated.is_empty() {
+                println!("Everything is up to date.");
+            }
+            for (path, changes) in migrated {
+                let action = if *check {
+                    "Needs migrating"
+                } else {
+                    "Migrated"
+                };
+                println!("{action}: {}", path.display());
+                for change in changes {
+                    println!("  {change}");
+                }
+            }
+        }
+
+        Commands::Export { file } => match export_state(file, &xdg) {
+            Ok(()) => {
+                println!("Exported to {file}");
+            }
+            Err(ExportError::ConfigError(config_error)) => {
+                handle_config_error(config_error);
+            }
+            Err(err) => {
+                println!("Error exporting: {err}");
+            }
         },
 
+        Commands::Restore { file, force } => {
+            let restored = match restore_state(file, *force, &xdg) {
+                Ok(restored) => restored,
+                Err(RestoreError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => {
+                    println!("Error restoring: {err}");
+                    std::process::exit(1);
+                }
+            };
+            for (project, status) in restored {
+                let status = match status {
+                    ProjectRestoreStatus::Cloned => "cloned",
+                    ProjectRestoreStatus::AlreadyExists => "already exists, linked",
+                    ProjectRestoreStatus::NoRemote => "no git remote, copy it over by hand",
+                    ProjectRestoreStatus::CloneFailed => "git clone failed",
+                };
+                println!("{project}: {status}");
+            }
+        }
+
+        Commands::Repair {
+            alias_group,
+            relative,
+            absolute,
+        } => {
+            let style = match (relative, absolute) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
+            match repair_alias_links(alias_group.as_deref(), style, &xdg) {
+                Ok(count) => {
+                    println!("Rewrote {count} aliases.");
+                }
+                Err(RepairAliasLinksError::ConfigError(config_error)) => {
+                    handle_config_error(config_error);
+                }
+                Err(err) => {
+                    println!("Error repairing aliases: {err}");
+                }
+            }
+        }
+
         Commands::Open { entity } => match entity {
             OpenEntity::Project {
                 name,
                 lib,
                 terminal,
+                params,
+                yes,
+                args,
             } => match terminal {
                 true => {
                     let path = match get_project_path(name, lib.as_deref(), &xdg) {
                         Ok(path) => path,
                         Err(GetProjectPathError::ConfigError(config_error)) => {
                             handle_config_error(config_error);
-                            return;
                         }
                         Err(err) => {
                             println!("Error getting project path: {err}");
@@ -799,17 +1744,41 @@ fn main() {
                     };
                     println!("{}", path.to_str().unwrap());
                 }
-                false => match open_project(name, lib.as_deref(), &xdg) {
-                    Ok(_) => {
-                        println!("Project '{name}' opened successfully.");
-                    }
-                    Err(OpenProjectError::ConfigError(config_error)) => {
-                        handle_config_error(config_error);
-                    }
-                    Err(err) => {
-                        println!("Error opening project: {err}");
+                false => {
+                    let args = ScriptArgs::new(args.clone())
+                        .with_params(params.clone())
+                        .with_assume_yes(*yes);
+                    match open_project(name, lib.as_deref(), &args, &xdg) {
+                        Ok(_) => {
+                            println!("Project '{name}' opened successfully.");
+                        }
+                        Err(OpenProjectError::ConfigError(config_error)) => {
+                            handle_config_error(config_error);
+                        }
+                        Err(OpenProjectError::OpenerRuntime(err)) => {
+                            handle_script_error(
+                                "Opener",
+                                &err.path,
+                                &err.message,
+                                err.traceback.as_deref(),
+                            );
+                        }
+                        Err(OpenProjectError::HookRuntime(err)) => {
+                            handle_script_error(
+                                &format!("Hook {}", err.hook),
+                                &err.path,
+                                &err.message,
+                                err.traceback.as_deref(),
+                            );
+                        }
+                        Err(OpenProjectError::Executable(err)) => {
+                            handle_executable_error("Opener", &err);
+                        }
+                        Err(err) => {
+                            println!("Error opening project: {err}");
+                        }
                     }
-                },
+                }
             },
 
             OpenEntity::Config { terminal } => {
@@ -828,6 +1797,17 @@ fn main() {
                         Err(OpenConfigError::ConfigError(config_error)) => {
                             handle_config_error(config_error);
                         }
+                        Err(OpenConfigError::OpenerRuntime(err)) => {
+                            handle_script_error(
+                                "Opener",
+                                &err.path,
+                                &err.message,
+                                err.traceback.as_deref(),
+                            );
+                        }
+                        Err(OpenConfigError::Executable(err)) => {
+                            handle_executable_error("Opener", &err);
+                        }
                         Err(OpenConfigError::ConfigVarNotDefined(var_name)) => {
                             println!("Config variable not defined: {var_name}, printing config path instead.");
                             print_config_path();
@@ -858,6 +1838,17 @@ fn main() {
                         Err(OpenBuildersError::ConfigError(config_error)) => {
                             handle_config_error(config_error);
                         }
+                        Err(OpenBuildersError::OpenerRuntime(err)) => {
+                            handle_script_error(
+                                "Opener",
+                                &err.path,
+                                &err.message,
+                                err.traceback.as_deref(),
+                            );
+                        }
+                        Err(OpenBuildersError::Executable(err)) => {
+                            handle_executable_error("Opener", &err);
+                        }
                         Err(OpenBuildersError::ConfigVarNotDefined(var_name)) => {
                             println!("Config variable not defined: {var_name}, printing builders path instead.");
                             print_builders_path();
@@ -888,6 +1879,17 @@ fn main() {
                         Err(OpenOpenersError::ConfigError(config_error)) => {
                             handle_config_error(config_error);
                         }
+                        Err(OpenOpenersError::OpenerRuntime(err)) => {
+                            handle_script_error(
+                                "Opener",
+                                &err.path,
+                                &err.message,
+                                err.traceback.as_deref(),
+                            );
+                        }
+                        Err(OpenOpenersError::Executable(err)) => {
+                            handle_executable_error("Opener", &err);
+                        }
                         Err(OpenOpenersError::ConfigVarNotDefined(var_name)) => {
                             println!("Config variable not defined: {var_name}, printing openers path instead.");
                             print_openers_path();
@@ -900,9 +1902,119 @@ fn main() {
             }
         },
 
-        Commands::Delete => {
-            // delete_project(name, &xdg);
-        }
+        Commands::Delete { entity } => match entity {
+            DeleteEntity::Library { name, yes } => {
+                let libraries = match get_libraries(&xdg) {
+                    Ok(libraries) => libraries,
+                    Err(GetLibsError::ConfigError(err)) => {
+                        handle_config_error(err);
+                    }
+                    Err(err) => {
+                        println!("Error getting libraries: {err}");
+                        return;
+                    }
+                };
+                let Some(lib_path) = libraries.get(name) else {
+                    println!("Library '{name}' not found.");
+                    return;
+                };
+                let aliases = match get_library_aliases(name, &xdg) {
+                    Ok(aliases) => aliases,
+                    Err(err) => {
+                        println!("Error getting library aliases: {err}");
+                        return;
+                    }
+                };
+                let projects = get_projects(&xdg).unwrap_or_default();
+
+                println!("This will delete the library '{name}' at {lib_path}");
+                for (project_name, (_, project_lib, _)) in &projects {
+                    if project_lib == name {
+                        println!("  project: {project_name}");
+                    }
+                }
+                for alias in &aliases {
+                    println!("  alias: {}", alias.display());
+                }
+                if !*yes {
+                    print!("Are you sure? [y/N] ");
+                    std::io::stdout().flush().unwrap();
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input).unwrap();
+                    let input = input.trim().to_lowercase();
+                    if input != "y" && input != "yes" {
+                        return;
+                    }
+                }
+                match delete_library(name, &xdg) {
+                    Ok(_) => {
+                        println!("Library '{name}' deleted successfully.");
+                    }
+                    Err(DeleteLibraryError::ConfigError(config_error)) => {
+                        handle_config_error(config_error);
+                    }
+                    Err(DeleteLibraryError::HookRuntime(err)) => {
+                        handle_script_error(
+                            &format!("Hook {}", err.hook),
+                            &err.path,
+                            &err.message,
+                            err.traceback.as_deref(),
+                        );
+                    }
+                    Err(DeleteLibraryError::Executable(err)) => {
+                        handle_executable_error("Hook", &err);
+                    }
+                    Err(err) => {
+                        println!("Error deleting library: {err}");
+                    }
+                }
+            }
+            DeleteEntity::Project { name, lib, yes } => {
+                let project_path = match get_project_path(name, lib.as_deref(), &xdg) {
+                    Ok(project_path) => project_path,
+                    Err(err) => {
+                        println!("Error finding project: {err}");
+                        return;
+                    }
+                };
+                println!(
+                    "This will delete the project '{name}' at {} and the aliases pointing into it",
+                    project_path.display()
+                );
+                if !*yes {
+                    print!("Are you sure? [y/N] ");
+                    std::io::stdout().flush().unwrap();
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input).unwrap();
+                    let input = input.trim().to_lowercase();
+                    if input != "y" && input != "yes" {
+                        return;
+                    }
+                }
+                match delete_project(name, lib.as_deref(), &xdg) {
+                    Ok(_) => {
+                        println!("Project '{name}' deleted successfully.");
+                    }
+                    Err(DeleteProjectError::ConfigError(config_error)) => {
+                        handle_config_error(config_error);
+                    }
+                    Err(DeleteProjectError::HookRuntime(err)) => {
+                        handle_script_error(
+                            &format!("Hook {}", err.hook),
+                            &err.path,
+                            &err.message,
+                            err.traceback.as_deref(),
+                        );
+                    }
+                    Err(DeleteProjectError::Executable(err)) => {
+                        handle_executable_error("Hook", &err);
+                    }
+                    Err(err) => {
+                        println!("Error deleting project: {err}");
+                    }
+                }
+            }
+        },
 
         Commands::Forget { entity } => match entity {
             ForgetEntity::AliasGroup { name } => match untrack_alias_group(name, &xdg) {
@@ -922,7 +2034,6 @@ fn main() {
                     Ok(libraries) => libraries,
                     Err(GetLibsError::ConfigError(err)) => {
                         handle_config_error(err);
-                        return;
                     }
                     Err(err) => {
                         println!("Error getting libraries: {err}");
@@ -951,7 +2062,6 @@ fn main() {
                     Ok(project_types) => project_types,
                     Err(GetProjectTypesError::ConfigError(err)) => {
                         handle_config_error(err);
-                        return;
                     }
                 };
                 if project_types.contains_key(name) {
@@ -0,0 +1,115 @@
+//! Upgrades config files written by older versions of donna to the current layout.
+//!
+//! Every file records the layout version it was written with in its `version` key, files without one are
+//! version 0. A migration upgrades a file from version `n` to `n + 1`, they are applied in order until the file
+//! is at the current version.
+
+use crate::errors::InvalidVersionError;
+use crate::utils::atomic_write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Current layout version of `config.toml`
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Current layout version of `.pm/project.toml`
+pub const PROJECT_CONFIG_VERSION: u32 = 1;
+
+/// Upgrades a file by one version and returns a description of what changed
+type Migration = fn(&mut Table) -> String;
+
+/// `CONFIG_MIGRATIONS[n]` upgrades a config from version `n` to `n + 1`
+const CONFIG_MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [config_v0_to_v1];
+
+/// `PROJECT_CONFIG_MIGRATIONS[n]` upgrades a project config from version `n` to `n + 1`
+const PROJECT_CONFIG_MIGRATIONS: [Migration; PROJECT_CONFIG_VERSION as usize] =
+    [project_config_v0_to_v1];
+
+fn config_v0_to_v1(_table: &mut Table) -> String {
+    "Added version key".to_string()
+}
+
+fn project_config_v0_to_v1(table: &mut Table) -> String {
+    // project configs written before alias groups were tracked don't have the key, but the api expects it
+    if table.contains_key("tracked_alias_groups") {
+        "Added version key".to_string()
+    } else {
+        table.insert("tracked_alias_groups".to_string(), Value::Array(Vec::new()));
+        "Added version key and empty tracked_alias_groups".to_string()
+    }
+}
+
+/// Get the layout version of a file, files without a version key are version 0
+pub fn get_version(table: &Table) -> Result<u32, InvalidVersionError> {
+    match table.get("version") {
+        None => Ok(0),
+        Some(Value::Integer(version)) => u32::try_from(*version)
+            .map_err(|_| InvalidVersionError(format!("{version} is not a valid layout version"))),
+        Some(version) => Err(InvalidVersionError(format!(
+            "{version} is not a valid layout version"
+        ))),
+    }
+}
+
+fn migrate(table: &mut Table, version: u32, migrations: &[Migration]) -> Result<Vec<String>, u32> {
+    if version as usize > migrations.len() {
+        return Err(version);
+    }
+    let mut changes = Vec::new();
+    for (from, migration) in migrations.iter().enumerate().skip(version as usize) {
+        let change = migration(table);
+        table.insert("version".to_string(), Value::Integer(from as i64 + 1));
+        changes.push(format!("v{from} -> v{}: {change}", from + 1));
+    }
+    Ok(changes)
+}
+
+/// Upgrade a config from `version` to the current version, returns the changes or the version if it is newer than
+/// supported
+pub fn migrate_config(table: &mut Table, version: u32) -> Result<Vec<String>, u32> {
+    migrate(table, version, &CONFIG_MIGRATIONS)
+}
+
+/// Upgrade a project config from `version` to the current version, returns the changes or the version if it is
+/// newer than supported
+pub fn migrate_project_config(table: &mut Table, version: u32) -> Result<Vec<String>, u32> {
+    migrate(table, version, &PROJECT_CONFIG_MIGRATIONS)
+}
+
+/// Get the path the original file is backed up to before it is migrated, e.g. `config.toml.v0.bak`
+pub fn get_backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(format!(".v{version}.bak"));
+    path.with_file_name(file_name)
+}
+
+/// Back up the original contents of a file before it is overwritten with a migrated version. Empty files are not
+/// backed up since there is nothing to lose.
+pub fn back_up(
+    path: &Path,
+    original_contents: &str,
+    original_version: u32,
+) -> Result<(), std::io::Error> {
+    if !original_contents.trim().is_empty() {
+        let backup_path = get_backup_path(path, original_version);
+        fs::write(&backup_path, original_contents)?;
+        log::info!(
+            "Backed up {} to {} before migrating",
+            path.display(),
+            backup_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Back up the original contents of a file and write the migrated table in its place
+pub fn write_migrated(
+    path: &Path,
+    original_contents: &str,
+    original_version: u32,
+    table: &Table,
+) -> Result<(), std::io::Error> {
+    back_up(path, original_contents, original_version)?;
+    atomic_write(path, toml::to_string(table).map_err(std::io::Error::other)?)
+}
@@ -0,0 +1,176 @@
+//! Questions scripts ask with `donna.prompt`, `donna.confirm` and `donna.select`.
+//!
+//! A question is answered by the `--param` value of its name if there is one, its name is its text unless a name
+//! is given. Otherwise it's asked on the terminal, and an empty answer takes the default. With `--yes`, or when
+//! stdin isn't a terminal, nothing is asked: confirmations are answered yes under `--yes` and every other question
+//! takes its default. A question without an answer is an error.
+
+use crate::errors::PromptError;
+use crate::lua_api::ScriptArgs;
+use std::io::{self, IsTerminal, Write};
+
+/// Ask for a line of text
+pub fn prompt(
+    text: &str,
+    default: Option<&str>,
+    name: Option<&str>,
+    args: &ScriptArgs,
+) -> Result<String, PromptError> {
+    if let Some(value) = get_param(text, name, args) {
+        return Ok(value.to_string());
+    }
+    if !is_interactive(args) {
+        return default
+            .map(str::to_string)
+            .ok_or_else(|| no_answer(text, name, args));
+    }
+    loop {
+        let answer = match default {
+            Some(default) => read_answer(&format!("{text} [{default}]"))?,
+            None => read_answer(text)?,
+        };
+        match (answer.is_empty(), default) {
+            (false, _) => return Ok(answer),
+            (true, Some(default)) => return Ok(default.to_string()),
+            (true, None) => continue,
+        }
+    }
+}
+
+/// Ask a yes or no question
+pub fn confirm(
+    text: &str,
+    default: Option<bool>,
+    name: Option<&str>,
+    args: &ScriptArgs,
+) -> Result<bool, PromptError> {
+    if let Some(value) = get_param(text, name, args) {
+        return parse_bool(value).ok_or_else(|| {
+            PromptError(format!(
+                "{} must be yes or no, got {value}",
+                get_name(text, name)
+            ))
+        });
+    }
+    if args.assume_yes {
+        return Ok(true);
+    }
+    if !is_interactive(args) {
+        return default.ok_or_else(|| no_answer(text, name, args));
+    }
+    let choices = match default {
+        Some(true) => "[Y/n]",
+        Some(false) => "[y/N]",
+        None => "[y/n]",
+    };
+    loop {
+        let answer = read_answer(&format!("{text} {choices}"))?;
+        match (parse_bool(&answer), default) {
+            (Some(answer), _) => return Ok(answer),
+            (None, Some(default)) if answer.is_empty() => return Ok(default),
+            _ => continue,
+        }
+    }
+}
+
+/// Ask to choose one of a list of options, the answer is the chosen option
+pub fn select(
+    text: &str,
+    options: &[String],
+    default: Option<&str>,
+    name: Option<&str>,
+    args: &ScriptArgs,
+) -> Result<String, PromptError> {
+    if options.is_empty() {
+        Err(PromptError(format!(
+            "{} has no options",
+            get_name(text, name)
+        )))?;
+    }
+    let check = |value: &str| match options.iter().any(|option| option == value) {
+        true => Ok(value.to_string()),
+        false => Err(PromptError(format!(
+            "{} must be one of {}, got {value}",
+            get_name(text, name),
+            options.join(", ")
+        ))),
+    };
+    if let Some(value) = get_param(text, name, args) {
+        return check(value);
+    }
+    if !is_interactive(args) {
+        return default
+            .ok_or_else(|| no_answer(text, name, args))
+            .and_then(check);
+    }
+    println!("{text}");
+    for (i, option) in options.iter().enumerate() {
+        println!("  {}) {option}", i + 1);
+    }
+    loop {
+        let answer = match default {
+            Some(default) => read_answer(&format!("Choose 1-{} [{default}]", options.len()))?,
+            None => read_answer(&format!("Choose 1-{}", options.len()))?,
+        };
+        if answer.is_empty() {
+            if let Some(default) = default {
+                return check(default);
+            }
+        }
+        let chosen = match answer.parse::<usize>() {
+            Ok(i) => i.checked_sub(1).and_then(|i| options.get(i)),
+            Err(_) => options.iter().find(|option| **option == answer),
+        };
+        if let Some(chosen) = chosen {
+            return Ok(chosen.clone());
+        }
+    }
+}
+
+/// Get the name `--param` values of a question are given by
+fn get_name<'a>(text: &'a str, name: Option<&'a str>) -> &'a str {
+    name.unwrap_or(text)
+}
+
+fn get_param<'a>(text: &str, name: Option<&str>, args: &'a ScriptArgs) -> Option<&'a str> {
+    args.params.get(get_name(text, name)).map(String::as_str)
+}
+
+fn is_interactive(args: &ScriptArgs) -> bool {
+    !args.assume_yes && io::stdin().is_terminal()
+}
+
+fn no_answer(text: &str, name: Option<&str>, args: &ScriptArgs) -> PromptError {
+    let reason = match args.assume_yes {
+        true => "--yes was given",
+        false => "stdin isn't a terminal",
+    };
+    PromptError(format!(
+        "{text} has no default and {reason}, answer it with --param '{}=VALUE'",
+        get_name(text, name)
+    ))
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "y" | "yes" | "true" => Some(true),
+        "n" | "no" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Print a question and read the answer from stdin
+fn read_answer(question: &str) -> Result<String, PromptError> {
+    print!("{question} ");
+    io::stdout()
+        .flush()
+        .map_err(|e| PromptError(e.to_string()))?;
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) => Err(PromptError(format!(
+            "stdin was closed before {question} was answered"
+        ))),
+        Ok(_) => Ok(input.trim().to_string()),
+        Err(e) => Err(PromptError(e.to_string())),
+    }
+}
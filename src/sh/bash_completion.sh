@@ -21,6 +21,16 @@ _donna_complete_projects() {
     COMPREPLY=($(compgen -W "$projects" -- "${COMP_WORDS[COMP_CWORD]}"))
 }
 
+_donna_complete_profiles() {
+    local profiles=$(donna _autocompletion-values profiles 2>/dev/null || echo "")
+    COMPREPLY=($(compgen -W "$profiles" -- "${COMP_WORDS[COMP_CWORD]}"))
+}
+
+_donna_complete_config_keys() {
+    local keys=$(donna _autocompletion-values config-keys 2>/dev/null || echo "")
+    COMPREPLY=($(compgen -W "$keys" -- "${COMP_WORDS[COMP_CWORD]}"))
+}
+
 # Override specific completions only where we need dynamic data
 _donna_override() {
     local cur prev words cword
@@ -29,8 +39,8 @@ _donna_override() {
     # Only handle specific cases that need dynamic completion
     case "${prev}" in
         # For flags that expect dynamic values
-        -g|--alias-groups)
-            if [[ "${words[*]}" == *"create project"* ]]; then
+        -g|--alias-groups|--alias-group)
+            if [[ "${words[*]}" == *"create project"* ]] || [[ "${words[*]}" == *"create alias "* ]]; then
                 _donna_complete_alias_groups
                 return 0
             fi
@@ -47,11 +57,15 @@ _donna_override() {
                 return 0
             fi
             ;;
+        --profile)
+            _donna_complete_profiles
+            return 0
+            ;;
     esac
 
     # Handle positional arguments that need dynamic completion
     case "${words[*]}" in
-        "donna open project "*)
+        "donna open project "*|"donna create alias "*)
             if [[ $cword -eq 3 ]]; then
                 _donna_complete_projects
                 return 0
@@ -81,6 +95,18 @@ _donna_override() {
                 return 0
             fi
             ;;
+        "donna config get "*|"donna config set "*|"donna config unset "*)
+            if [[ $cword -eq 3 ]]; then
+                _donna_complete_config_keys
+                return 0
+            fi
+            ;;
+        "donna profile switch "*)
+            if [[ $cword -eq 3 ]]; then
+                _donna_complete_profiles
+                return 0
+            fi
+            ;;
     esac
 
     # Let the original clap completion handle everything else
@@ -21,6 +21,16 @@ _donna_projects() {
     _describe 'projects' projects
 }
 
+_donna_profiles() {
+    local profiles=(${(f)"$(donna _autocompletion-values profiles 2>/dev/null)"})
+    _describe 'profiles' profiles
+}
+
+_donna_config_keys() {
+    local keys=(${(f)"$(donna _autocompletion-values config-keys 2>/dev/null)"})
+    _describe 'config keys' keys
+}
+
 # Override specific completions
 _donna_override() {
     local context curcontext="$curcontext" state line
@@ -28,8 +38,8 @@ _donna_override() {
 
     # Handle specific cases that need dynamic completion
     case "$words[$CURRENT-1]" in
-        -g|--alias-groups)
-            if [[ "$words[*]" == *"create project"* ]]; then
+        -g|--alias-groups|--alias-group)
+            if [[ "$words[*]" == *"create project"* ]] || [[ "$words[*]" == *"create alias "* ]]; then
                 _donna_alias_groups
                 return 0
             fi
@@ -46,11 +56,15 @@ _donna_override() {
                 return 0
             fi
             ;;
+        --profile)
+            _donna_profiles
+            return 0
+            ;;
     esac
 
     # Handle positional arguments
     case "$words[*]" in
-        "donna open project "*|*"open project "*)
+        "donna open project "*|*"open project "*|"donna create alias "*|*"create alias "*)
             if [[ $CURRENT -eq 4 ]]; then
                 _donna_projects
                 return 0
@@ -80,6 +94,18 @@ _donna_override() {
                 return 0
             fi
             ;;
+        "donna config get "*|*"config get "*|"donna config set "*|*"config set "*|"donna config unset "*|*"config unset "*)
+            if [[ $CURRENT -eq 4 ]]; then
+                _donna_config_keys
+                return 0
+            fi
+            ;;
+        "donna profile switch "*|*"profile switch "*)
+            if [[ $CURRENT -eq 4 ]]; then
+                _donna_profiles
+                return 0
+            fi
+            ;;
     esac
 
     # Let the original clap completion handle everything else
@@ -0,0 +1,6 @@
+-- Example builder, installed by `donna init`. Builders run when a project of a type that uses them is created,
+-- with the project described by the PM table, e.g. PM.name, PM.path, PM.type and PM.library.
+
+local readme = io.open(PM.path .. "/README.md", "w")
+readme:write("# " .. PM.name .. "\n")
+readme:close()
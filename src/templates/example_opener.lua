@@ -0,0 +1,5 @@
+-- Example opener, installed by `donna init`. Openers run on `donna open project`, with the project described by
+-- the PM table, e.g. PM.name, PM.path, PM.type and PM.library.
+
+print("Opening " .. PM.name .. " in " .. PM.path)
+donna.exec{"ls", capture = false}
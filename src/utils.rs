@@ -1,6 +1,16 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
+use std::ffi::OsString;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs::symlink;
+
+#[cfg(windows)]
+use std::os::windows::fs::symlink_dir as symlink;
 
 use crate::errors;
 
@@ -10,10 +20,13 @@ pub mod types {
     pub type LibraryName = String;
 }
 
+#[derive(Clone, Debug)]
 pub struct XDG {
     pub home_var_name: String,
     pub config_home_name: String,
     pub data_home_name: String,
+    /// Named profile to use instead of the default config, see `with_profile`
+    pub profile: Option<String>,
 }
 impl XDG {
     pub fn new(
@@ -25,9 +38,22 @@ impl XDG {
             home_var_name: home_var_name.unwrap_or("HOME").to_string(),
             config_home_name: config_home_name.unwrap_or("XDG_CONFIG_HOME").to_string(),
             data_home_name: data_home_name.unwrap_or("XDG_DATA_HOME").to_string(),
+            profile: None,
         }
     }
 
+    /// Use a named profile, which has its own config and default library. `None` and `"default"` both use the
+    /// default config.
+    pub fn with_profile(mut self, profile: Option<&str>) -> Self {
+        self.profile = profile
+            .filter(|name| *name != Self::DEFAULT_PROFILE)
+            .map(str::to_string);
+        self
+    }
+
+    /// Name of the profile that uses the default config
+    pub const DEFAULT_PROFILE: &'static str = "default";
+
     pub fn get_config_home(&self) -> String {
         match env::var(&self.config_home_name) {
             Ok(val) => val,
@@ -58,6 +84,57 @@ impl XDG {
     }
 }
 
+/// Expand `~` at the start of a path and `$VAR` or `${VAR}` anywhere in it, `$$` is a literal `$`. Variables are
+/// looked up with `lookup`, `~` is the variable `HOME`. Returns the name of the first variable that isn't defined.
+pub fn expand_path(path: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = path;
+    if rest == "~" || rest.starts_with("~/") {
+        expanded.push_str(&lookup("HOME").ok_or("HOME")?);
+        rest = &rest[1..];
+    }
+    while let Some(i) = rest.find('$') {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let name = if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced.find('}').ok_or_else(|| braced.to_string())?;
+            rest = &braced[end + 1..];
+            &braced[..end]
+        } else if let Some(after) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+            continue;
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            name
+        };
+        if name.is_empty() {
+            expanded.push('$');
+            continue;
+        }
+        expanded.push_str(&lookup(name).ok_or_else(|| name.to_string())?);
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Escape a path so `expand_path` reads it back unchanged, `$` is written as `$$` and a leading `~` as `./~`.
+/// Paths that `expand_path` doesn't change are returned as they are.
+pub fn escape_path(path: &str) -> String {
+    if expand_path(path, |_| Some(String::new())).as_deref() == Ok(path) {
+        return path.to_string();
+    }
+    let escaped = path.replace('$', "$$");
+    match escaped == "~" || escaped.starts_with("~/") {
+        true => format!("./{escaped}"),
+        false => escaped,
+    }
+}
+
 pub fn to_full_path(path: &str) -> PathBuf {
     let cwd = env::current_dir().unwrap();
     let full_path = Path::new(path);
@@ -114,3 +191,143 @@ pub fn delete(path: &str) -> Result<(), errors::DeleteError> {
     }
     Ok(())
 }
+
+/// Create a symlink at `link` pointing to `target`. If `relative` is true the link stores the path
+/// to `target` relative to the directory containing the link, so the tree can be moved as a whole.
+pub fn create_link(target: &Path, link: &Path, relative: bool) -> io::Result<()> {
+    if !relative {
+        return symlink(target, link);
+    }
+    // canonicalize both sides so symlinked parent directories don't produce a wrong relative path
+    let link_dir = fs::canonicalize(link.parent().unwrap_or(Path::new(".")))?;
+    let target = fs::canonicalize(target)?;
+    let relative_target = pathdiff::diff_paths(&target, &link_dir).ok_or_else(|| {
+        io::Error::other(format!(
+            "Could not make {} relative to {}",
+            target.display(),
+            link_dir.display()
+        ))
+    })?;
+    symlink(relative_target, link)
+}
+
+/// Get the absolute path a symlink points to, relative links are resolved against the link's directory.
+/// The target does not need to exist.
+pub fn resolve_link(link: &Path) -> io::Result<PathBuf> {
+    let target = fs::read_link(link)?;
+    if target.is_absolute() {
+        return Ok(target);
+    }
+    let link_dir = fs::canonicalize(link.parent().unwrap_or(Path::new(".")))?;
+    let mut resolved = PathBuf::new();
+    for component in link_dir.join(target).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Move a directory, falling back to copying and removing the original when the destination is on another device.
+pub fn move_dir(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_dir_all(from, to)?;
+            fs::remove_dir_all(from)
+        }
+        result => result,
+    }
+}
+
+/// Recursively copy a directory, symlinks are copied as links rather than followed.
+fn copy_dir_all(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let dest = to.join(entry.file_name());
+        if file_type.is_symlink() {
+            symlink(fs::read_link(entry.path())?, dest)?;
+        } else if file_type.is_dir() {
+            copy_dir_all(&entry.path(), &dest)?;
+        } else {
+            fs::copy(entry.path(), dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a file by writing to a temporary file next to it and renaming it into place, so other processes never
+/// see a partially written file. If the path is a symlink the file it points to is replaced.
+pub fn atomic_write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+thread_local! {
+    /// Number of `FileLock`s each thread holds per lock file
+    static HELD_LOCKS: RefCell<HashMap<PathBuf, usize>> = RefCell::new(HashMap::new());
+}
+
+/// An exclusive advisory lock on a file, released when dropped. Acquiring a lock the current thread already holds
+/// doesn't block, so functions that hold the lock can call each other.
+pub struct FileLock {
+    path: PathBuf,
+    // only the outermost lock holds the file, closing it releases the lock
+    _file: Option<fs::File>,
+}
+
+impl FileLock {
+    /// Block until the lock on `path` is acquired, the file is created if it doesn't exist.
+    pub fn acquire(path: &Path) -> io::Result<FileLock> {
+        let held = HELD_LOCKS.with(|locks| locks.borrow().get(path).copied().unwrap_or(0));
+        let file = if held == 0 {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(path)?;
+            file.lock()?;
+            Some(file)
+        } else {
+            None
+        };
+        HELD_LOCKS.with(|locks| *locks.borrow_mut().entry(path.to_path_buf()).or_insert(0) += 1);
+        Ok(FileLock {
+            path: path.to_path_buf(),
+            _file: file,
+        })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        HELD_LOCKS.with(|locks| {
+            let mut locks = locks.borrow_mut();
+            if let Some(count) = locks.get_mut(&self.path) {
+                *count -= 1;
+                if *count == 0 {
+                    locks.remove(&self.path);
+                }
+            }
+        });
+    }
+}
@@ -3,7 +3,7 @@ use donna::{
     get_alias_groups, get_libraries, get_project_path, get_project_types, get_projects,
     open_project, set_builders_path_prefix, set_default_lib, set_openers_path_prefix,
     untrack_alias_group, untrack_library, untrack_project_type, update_alias_group, Config,
-    ProjectConfig, XDG,
+    ProjectConfig, ScriptArgs, XDG,
 };
 use std::{fs, path::PathBuf};
 
@@ -26,7 +26,7 @@ fn test_define_project_type() {
     let result = define_project_type(
         "rust",
         Some(vec!["code".to_string()]),
-        Some("lua/builder.lua"),
+        Some(vec!["lua/builder.lua".to_string()]),
         Some("lua/opener.lua"),
         false,
         &xdg,
@@ -41,7 +41,7 @@ fn test_define_project_type() {
         "code"
     );
     assert_eq!(
-        project_type.builder.as_ref().unwrap(),
+        project_type.builder.as_ref().unwrap().to_string(),
         PathBuf::from(xdg.get_data_home())
             .join("project_manager")
             .join("builders")
@@ -153,7 +153,14 @@ fn test_create_lib() {
 
     // Test basic library creation
     let lib_path = gen_test_home_path(unique_name).join("lib-dir");
-    let result = create_lib("main-lib", lib_path.to_str().unwrap(), true, false, &xdg);
+    let result = create_lib(
+        "main-lib",
+        lib_path.to_str().unwrap(),
+        true,
+        false,
+        None,
+        &xdg,
+    );
     assert!(result.is_ok());
     assert!(lib_path.exists());
 
@@ -173,6 +180,7 @@ fn test_create_lib() {
         secondary_lib_path.to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     );
     assert!(result.is_ok());
@@ -188,6 +196,7 @@ fn test_create_lib() {
         another_path.to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -200,6 +209,7 @@ fn test_create_lib() {
         another_path.to_str().unwrap(),
         false,
         true,
+        None,
         &xdg,
     );
     assert!(result.is_ok());
@@ -211,6 +221,7 @@ fn test_create_lib() {
         non_existent.to_str().unwrap(),
         false,
         true,
+        None,
         &xdg,
     );
     assert!(err_result.is_err());
@@ -230,10 +241,27 @@ fn test_create_project_basic() {
 
     // Create a library first
     let lib_path = gen_test_home_path(unique_name).join("lib-dir");
-    create_lib("main-lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib(
+        "main-lib",
+        lib_path.to_str().unwrap(),
+        true,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
 
     // Test basic project creation
-    let result = create_project("basic-project", None, None, None, false, None, &xdg);
+    let result = create_project(
+        "basic-project",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    );
     assert!(result.is_ok());
 
     // Verify project was created in the default library
@@ -263,7 +291,7 @@ fn test_create_project_with_type() {
 
     // Create a library
     let lib_path = gen_test_home_path(unique_name).join("lib");
-    create_lib("lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
 
     // Create a project type
     define_project_type("custom-type", None, None, None, false, &xdg).unwrap();
@@ -276,6 +304,7 @@ fn test_create_project_with_type() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     );
     assert!(result.is_ok());
@@ -298,6 +327,7 @@ fn test_create_project_with_type() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     );
     assert!(err_result.is_err());
@@ -317,7 +347,7 @@ fn test_create_project_with_alias_groups() {
 
     // Create a library
     let lib_path = gen_test_home_path(unique_name).join("lib");
-    create_lib("lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
 
     // Create alias groups
     let alias_path = gen_test_home_path(unique_name).join("aliases");
@@ -337,6 +367,7 @@ fn test_create_project_with_alias_groups() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     );
     assert!(result.is_ok());
@@ -367,6 +398,7 @@ fn test_create_project_with_alias_groups() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     );
     assert!(err_result.is_err());
@@ -392,6 +424,7 @@ fn test_create_project_in_specific_lib() {
         default_lib_path.to_str().unwrap(),
         true,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -400,12 +433,23 @@ fn test_create_project_in_specific_lib() {
         other_lib_path.to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
 
     // Create project in default lib
-    create_project("default-project", None, None, None, false, None, &xdg).unwrap();
+    create_project(
+        "default-project",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
 
     // Create project in specific lib
     create_project(
@@ -415,6 +459,7 @@ fn test_create_project_in_specific_lib() {
         Some("other-lib"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -431,6 +476,7 @@ fn test_create_project_in_specific_lib() {
         Some("non-existent"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     );
     assert!(err_result.is_err());
@@ -450,18 +496,46 @@ fn test_create_project_already_exists() {
 
     // Create a library
     let lib_path = gen_test_home_path(unique_name).join("lib");
-    create_lib("lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
 
     // Create project
-    create_project("existing", None, None, None, false, None, &xdg).unwrap();
+    create_project(
+        "existing",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
 
     // Create project with same name but already_exists=false (should fail)
-    let err_result = create_project("existing", None, None, None, false, None, &xdg);
+    let err_result = create_project(
+        "existing",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    );
     assert!(err_result.is_err());
 
     // Create project with already_exists=true
     fs::create_dir_all(lib_path.join("manual-project")).unwrap();
-    let result = create_project("manual-project", None, None, None, true, None, &xdg);
+    let result = create_project(
+        "manual-project",
+        None,
+        None,
+        None,
+        true,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    );
     assert!(result.is_ok());
     assert!(lib_path.join("manual-project/.pm/project.toml").exists());
 }
@@ -483,21 +557,31 @@ fn test_open_project() {
 
     // Create a library
     let lib_path = gen_test_home_path(unique_name).join("lib");
-    create_lib("lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
 
     // Create a project
-    create_project("basic", None, None, None, false, None, &xdg).unwrap();
+    create_project(
+        "basic",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
 
     // Test opening non-existent project (should fail)
-    let err_result = open_project("non-existent", None, &xdg);
+    let err_result = open_project("non-existent", None, &ScriptArgs::default(), &xdg);
     assert!(err_result.is_err());
 
     // Test opening project in non-existent lib (should fail)
-    let err_result = open_project("basic", Some("non-existent"), &xdg);
+    let err_result = open_project("basic", Some("non-existent"), &ScriptArgs::default(), &xdg);
     assert!(err_result.is_err());
 
     // Test opening existing project (should work but not open anything since we don't have an opener script)
-    let result = open_project("basic", None, &xdg);
+    let result = open_project("basic", None, &ScriptArgs::default(), &xdg);
     assert!(result.is_ok());
 }
 
@@ -515,10 +599,20 @@ fn test_get_project_path() {
 
     // Create a library
     let lib_path = gen_test_home_path(unique_name).join("lib");
-    create_lib("lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
 
     // Create a project
-    create_project("path-test", None, None, None, false, None, &xdg).unwrap();
+    create_project(
+        "path-test",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
 
     // Test getting path of existing project
     let result = get_project_path("path-test", None, &xdg);
@@ -602,7 +696,7 @@ fn test_untrack_alias_group() {
 
     // Create a library
     let lib_path = gen_test_home_path(unique_name).join("lib");
-    create_lib("lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
 
     // Create a project with this alias group
     create_project(
@@ -612,6 +706,7 @@ fn test_untrack_alias_group() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -722,6 +817,7 @@ fn test_untrack_library() {
         lib_path.to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -730,6 +826,7 @@ fn test_untrack_library() {
         default_lib_path.to_str().unwrap(),
         true,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -765,7 +862,7 @@ fn test_untrack_project_type() {
 
     // Create a library
     let lib_path = gen_test_home_path(unique_name).join("lib");
-    create_lib("lib", lib_path.to_str().unwrap(), true, false, &xdg).unwrap();
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
 
     // Create a project with this type
     create_project(
@@ -775,6 +872,7 @@ fn test_untrack_project_type() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -818,8 +916,16 @@ fn test_get_projects() {
     // Create libraries
     let lib1_path = gen_test_home_path(unique_name).join("lib1");
     let lib2_path = gen_test_home_path(unique_name).join("lib2");
-    create_lib("lib1", lib1_path.to_str().unwrap(), true, false, &xdg).unwrap();
-    create_lib("lib2", lib2_path.to_str().unwrap(), false, false, &xdg).unwrap();
+    create_lib("lib1", lib1_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
+    create_lib(
+        "lib2",
+        lib2_path.to_str().unwrap(),
+        false,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
 
     // Create project types
     define_project_type("type1", None, None, None, false, &xdg).unwrap();
@@ -833,6 +939,7 @@ fn test_get_projects() {
         Some("lib1"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -843,6 +950,7 @@ fn test_get_projects() {
         Some("lib1"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -853,10 +961,21 @@ fn test_get_projects() {
         Some("lib2"),
         false,
         None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "project4",
+        None,
+        None,
+        Some("lib2"),
+        false,
+        None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
-    create_project("project4", None, None, Some("lib2"), false, None, &xdg).unwrap();
 
     // Test getting all projects
     let result = get_projects(&xdg);
@@ -892,8 +1011,16 @@ fn test_get_libraries() {
     // Create libraries
     let lib1_path = gen_test_home_path(unique_name).join("lib1");
     let lib2_path = gen_test_home_path(unique_name).join("lib2");
-    create_lib("lib1", lib1_path.to_str().unwrap(), true, false, &xdg).unwrap();
-    create_lib("lib2", lib2_path.to_str().unwrap(), false, false, &xdg).unwrap();
+    create_lib("lib1", lib1_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
+    create_lib(
+        "lib2",
+        lib2_path.to_str().unwrap(),
+        false,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
 
     // Test getting all libraries
     let result = get_libraries(&xdg);
@@ -959,7 +1086,7 @@ fn test_get_project_types() {
     define_project_type(
         "type1",
         Some(vec!["alias1".to_string()]),
-        Some("builder.lua"),
+        Some(vec!["builder.lua".to_string()]),
         Some("opener.lua"),
         false,
         &xdg,
@@ -979,7 +1106,7 @@ fn test_get_project_types() {
     let type1 = &types["type1"];
     assert_eq!(type1.default_alias_groups.as_ref().unwrap()[0], "alias1");
     assert_eq!(
-        type1.builder.as_ref().unwrap(),
+        type1.builder.as_ref().unwrap().to_string(),
         PathBuf::from(xdg.get_data_home())
             .join("project_manager")
             .join("builders")
@@ -1082,8 +1209,16 @@ fn test_set_default_lib() {
     // Create libraries
     let lib1_path = gen_test_home_path(unique_name).join("lib1");
     let lib2_path = gen_test_home_path(unique_name).join("lib2");
-    create_lib("lib1", lib1_path.to_str().unwrap(), true, false, &xdg).unwrap();
-    create_lib("lib2", lib2_path.to_str().unwrap(), false, false, &xdg).unwrap();
+    create_lib("lib1", lib1_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
+    create_lib(
+        "lib2",
+        lib2_path.to_str().unwrap(),
+        false,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
 
     // Test setting default lib
     let result = set_default_lib("lib2", &xdg);
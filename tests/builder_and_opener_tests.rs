@@ -2,8 +2,14 @@ mod utils;
 use utils::{gen_test_home_path, setup_home};
 
 use donna::{
-    create_alias_group, create_lib, create_project, define_project_type, open_project,
-    ProjectConfig, XDG,
+    add_to_alias_group, archive_project, create_alias_group, create_lib, create_project,
+    define_project_type, delete_library, delete_project,
+    errors::{
+        ArchiveProjectError, CreateProjectError, DeleteLibraryError, DeleteProjectError,
+        OpenProjectError,
+    },
+    get_libraries, open_project, set_config_value, update_library, update_project, ProjectConfig,
+    ScriptArgs, XDG,
 };
 
 use std::{fs, io::Write};
@@ -51,11 +57,12 @@ fn test_opener_with_only_defaults() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
 
-    open_project("test-proj", None, &xdg).unwrap();
+    open_project("test-proj", None, &ScriptArgs::default(), &xdg).unwrap();
 
     let proof_path =
         home_dir_path.join(".local/share/project_manager/projects/test-proj/proof.txt");
@@ -72,7 +79,7 @@ fn test_opener_with_only_defaults() {
                 .to_str()
                 .unwrap(),
             "test-type",
-            "nil"
+            "default"
         )
     );
 
@@ -89,7 +96,7 @@ fn test_opener_with_only_defaults() {
         project_config.opener.as_deref(),
         home_dir_path.join("opener.lua").to_str()
     );
-    assert_eq!(project_config.builder.as_deref(), None);
+    assert_eq!(project_config.builder, None);
 }
 
 #[test]
@@ -139,6 +146,7 @@ fn test_opener() {
         home_dir_path.join("test-lib").to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -150,10 +158,11 @@ fn test_opener() {
         Some("test-lib"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
-    open_project("test-proj", Some("test-lib"), &xdg).unwrap();
+    open_project("test-proj", Some("test-lib"), &ScriptArgs::default(), &xdg).unwrap();
 
     let proof_path = home_dir_path.join("test-lib/test-proj/proof.txt");
     assert!(proof_path.exists());
@@ -183,7 +192,7 @@ fn test_opener() {
         project_config.opener.as_deref(),
         home_dir_path.join("opener.lua").to_str()
     );
-    assert_eq!(project_config.builder.as_deref(), None);
+    assert_eq!(project_config.builder, None);
 }
 
 #[test]
@@ -229,11 +238,12 @@ fn test_builder_with_only_defaults() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
 
-    open_project("test-proj", None, &xdg).unwrap();
+    open_project("test-proj", None, &ScriptArgs::default(), &xdg).unwrap();
 
     let proof_path =
         home_dir_path.join(".local/share/project_manager/projects/test-proj/proof.txt");
@@ -250,7 +260,7 @@ fn test_builder_with_only_defaults() {
                 .to_str()
                 .unwrap(),
             "test-type",
-            "nil"
+            "default"
         )
     );
 
@@ -267,7 +277,7 @@ fn test_builder_with_only_defaults() {
         project_config.opener.as_deref(),
         home_dir_path.join("builder.lua").to_str()
     );
-    assert_eq!(project_config.builder.as_deref(), None);
+    assert_eq!(project_config.builder, None);
 }
 
 #[test]
@@ -294,12 +304,11 @@ fn test_builder() {
     define_project_type(
         "test-type",
         None,
-        Some(
-            gen_test_home_path(unique_name)
-                .join("builder.lua")
-                .to_str()
-                .unwrap(),
-        ),
+        Some(vec![gen_test_home_path(unique_name)
+            .join("builder.lua")
+            .to_str()
+            .unwrap()
+            .to_string()]),
         None,
         false,
         &xdg,
@@ -317,6 +326,7 @@ fn test_builder() {
         home_dir_path.join("test-lib").to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -328,6 +338,7 @@ fn test_builder() {
         Some("test-lib"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -358,8 +369,1125 @@ fn test_builder() {
     .unwrap();
     assert_eq!(project_config.project_type.as_deref(), Some("test-type"));
     assert_eq!(
-        project_config.builder.as_deref(),
+        project_config
+            .builder
+            .as_ref()
+            .map(ToString::to_string)
+            .as_deref(),
         home_dir_path.join("builder.lua").to_str()
     );
     assert_eq!(project_config.opener.as_deref(), None);
 }
+
+#[test]
+fn test_lua_module() {
+    let unique_name = "test_lua_module";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    create_alias_group(
+        "docs",
+        home_dir_path.join("docs").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    fs::write(
+        home_dir_path.join("builder.lua"),
+        r#"
+        local docs = PM_PROJECT_NAME .. "-docs"
+        donna.create_project{name = docs}
+        donna.add_to_group(docs, "docs")
+        local project = donna.project(docs)
+        local proof = io.open(PM_PROJECT_PATH .. "/proof.txt", "w")
+        proof:write(#donna.projects(), ", ", project.library, ", ", project.alias_groups[1], ", ",
+            tostring(donna.project("missing")), ", ", donna.alias_groups().docs, ", ",
+            tostring(donna.libraries().default ~= nil), ", ", donna.project(docs, "default").name, ", ",
+            tostring(pcall(donna.project, docs, "missing")))
+        proof:close()
+        "#,
+    )
+    .unwrap();
+    define_project_type(
+        "app",
+        None,
+        Some(vec![home_dir_path
+            .join("builder.lua")
+            .to_str()
+            .unwrap()
+            .to_string()]),
+        None,
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "site",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    let projects_path = home_dir_path.join(".local/share/project_manager/projects");
+    assert!(projects_path.join("site-docs").is_dir());
+    assert!(fs::symlink_metadata(home_dir_path.join("docs/site-docs")).is_ok());
+    assert_eq!(
+        fs::read_to_string(projects_path.join("site/proof.txt")).unwrap(),
+        format!(
+            "2, default, docs, nil, {}, true, site-docs, false",
+            home_dir_path.join("docs").to_str().unwrap()
+        )
+    );
+
+    // errors from the api are raised in the script
+    fs::write(
+        home_dir_path.join("builder.lua"),
+        r#"if pcall(donna.open, "missing") then error("open should fail") end"#,
+    )
+    .unwrap();
+    create_project(
+        "other",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_script_errors() {
+    let unique_name = "test_script_errors";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let builder_path = home_dir_path.join("builder.lua");
+    let opener_path = home_dir_path.join("opener.lua");
+    fs::write(
+        &builder_path,
+        "local function fail() error(\"boom\") end\nfail()\n",
+    )
+    .unwrap();
+    fs::write(&opener_path, "this is not lua").unwrap();
+    define_project_type(
+        "broken",
+        None,
+        Some(vec![builder_path.to_str().unwrap().to_string()]),
+        Some(opener_path.to_str().unwrap()),
+        false,
+        &xdg,
+    )
+    .unwrap();
+
+    match create_project(
+        "built",
+        Some("broken"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    ) {
+        Err(CreateProjectError::BuilderRuntime(err)) => {
+            assert_eq!(err.path, builder_path.to_str().unwrap());
+            assert!(
+                err.message.contains("builder.lua:1: boom"),
+                "{}",
+                err.message
+            );
+            assert!(err.traceback.unwrap().contains("in local 'fail'"));
+        }
+        result => panic!("Expected a builder error, got {result:?}"),
+    }
+
+    // already existing projects don't run the builder
+    fs::create_dir_all(home_dir_path.join(".local/share/project_manager/projects/opened")).unwrap();
+    create_project(
+        "opened",
+        Some("broken"),
+        None,
+        None,
+        true,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    match open_project("opened", None, &ScriptArgs::default(), &xdg) {
+        Err(OpenProjectError::OpenerRuntime(err)) => {
+            assert_eq!(err.path, opener_path.to_str().unwrap());
+            assert!(err.message.contains("syntax error"), "{}", err.message);
+        }
+        result => panic!("Expected an opener error, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_script_context() {
+    let unique_name = "test_script_context";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let script = r#"
+        local groups = {}
+        for group, link in pairs(PM.alias_groups) do table.insert(groups, group .. "=" .. link) end
+        table.sort(groups)
+        local metadata = PM.metadata and PM.metadata.tags[2] or "none"
+        local proof = io.open(PM.path .. "/" .. PM.action .. ".txt", "w")
+        proof:write(PM.name, ", ", PM.library, ", ", PM.library_path, ", ", PM.type, ", ",
+            table.concat(groups, " "), ", ", metadata, ", ", PM_PROJECT_LIB, ", ", PM_ALIAS_GROUP, ", ",
+            tostring(PM.version ~= nil))
+        proof:close()
+    "#;
+    fs::write(home_dir_path.join("script.lua"), script).unwrap();
+    let script_path = home_dir_path.join("script.lua");
+    for group in ["docs", "work"] {
+        create_alias_group(
+            group,
+            home_dir_path.join(group).to_str().unwrap(),
+            false,
+            &xdg,
+        )
+        .unwrap();
+    }
+    define_project_type(
+        "app",
+        Some(vec!["work".to_string(), "docs".to_string()]),
+        Some(vec![script_path.to_str().unwrap().to_string()]),
+        Some(script_path.to_str().unwrap()),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "site",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    let lib_path = home_dir_path.join(".local/share/project_manager/projects");
+    let project_path = lib_path.join("site");
+    let expected = |metadata: &str| {
+        format!(
+            "site, default, {}, app, docs={} work={}, {metadata}, default, docs, true",
+            lib_path.to_str().unwrap(),
+            home_dir_path.join("docs/site").to_str().unwrap(),
+            home_dir_path.join("work/site").to_str().unwrap(),
+        )
+    };
+    assert_eq!(
+        fs::read_to_string(project_path.join("build.txt")).unwrap(),
+        expected("none")
+    );
+
+    let project_config_path = project_path.join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+    let mut project_config = ProjectConfig::load(project_config_path.to_str().unwrap()).unwrap();
+    project_config.metadata = Some(toml::from_str(r#"tags = ["web", "blog"]"#).unwrap());
+    project_config
+        .save(project_config_path.to_str().unwrap())
+        .unwrap();
+    open_project("site", None, &ScriptArgs::default(), &xdg).unwrap();
+    assert_eq!(
+        fs::read_to_string(project_path.join("open.txt")).unwrap(),
+        expected("blog")
+    );
+}
+
+#[test]
+fn test_lua_init_and_modules() {
+    let unique_name = "test_lua_init_and_modules";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let data_path = home_dir_path.join(".local/share/project_manager");
+    fs::create_dir_all(data_path.join("lua")).unwrap();
+    fs::write(
+        data_path.join("init.lua"),
+        r#"function write_proof(text)
+            local proof = io.open(PM.path .. "/proof.txt", "w")
+            proof:write(text)
+            proof:close()
+        end"#,
+    )
+    .unwrap();
+    fs::write(
+        data_path.join("lua/greeting.lua"),
+        r#"return { greet = function(name) return "hello " .. name end }"#,
+    )
+    .unwrap();
+    let builder_path = home_dir_path.join("builder.lua");
+    fs::write(
+        &builder_path,
+        r#"write_proof(require("greeting").greet(PM.name))"#,
+    )
+    .unwrap();
+    define_project_type(
+        "app",
+        None,
+        Some(vec![builder_path.to_str().unwrap().to_string()]),
+        None,
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "site",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(data_path.join("projects/site/proof.txt")).unwrap(),
+        "hello site"
+    );
+
+    // errors in the init script are reported with its path
+    fs::write(data_path.join("init.lua"), r#"error("bad init")"#).unwrap();
+    match create_project(
+        "other",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    ) {
+        Err(CreateProjectError::BuilderRuntime(err)) => {
+            assert_eq!(err.path, data_path.join("init.lua").to_str().unwrap());
+            assert!(err.message.contains("bad init"), "{}", err.message);
+        }
+        result => panic!("Expected an init script error, got {result:?}"),
+    }
+
+    // a missing default init script is skipped, one set with lua_init is reported
+    fs::remove_file(data_path.join("init.lua")).unwrap();
+    fs::write(&builder_path, "").unwrap();
+    create_project(
+        "plain",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    let missing_init = home_dir_path.join("missing.lua");
+    set_config_value("lua_init", &[missing_init.to_str().unwrap()], &xdg).unwrap();
+    match create_project(
+        "missing",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    ) {
+        Err(CreateProjectError::BuilderRuntime(err)) => {
+            assert_eq!(err.path, missing_init.to_str().unwrap());
+        }
+        result => panic!("Expected a missing init script error, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_script_args() {
+    let unique_name = "test_script_args";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let script_path = home_dir_path.join("script.lua");
+    fs::write(
+        &script_path,
+        r#"
+        local proof = io.open(PM.path .. "/" .. PM.action .. ".txt", "w")
+        proof:write(table.concat(PM_ARGS, " "), ", ", tostring(PM_OPTIONS["new-window"]), ", ",
+            tostring(PM.options.mode), ", ", tostring(PM.options.file), ", ", #PM.args)
+        proof:close()
+        "#,
+    )
+    .unwrap();
+    define_project_type(
+        "app",
+        None,
+        Some(vec![script_path.to_str().unwrap().to_string()]),
+        Some(script_path.to_str().unwrap()),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "site",
+        Some("app"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    let project_path = home_dir_path.join(".local/share/project_manager/projects/site");
+    assert_eq!(
+        fs::read_to_string(project_path.join("build.txt")).unwrap(),
+        ", nil, nil, nil, 0"
+    );
+
+    let args = ScriptArgs::new(
+        ["--new-window", "--mode=debug", "main.rs", "--", "--file=x"]
+            .map(String::from)
+            .to_vec(),
+    );
+    open_project("site", None, &args, &xdg).unwrap();
+    assert_eq!(
+        fs::read_to_string(project_path.join("open.txt")).unwrap(),
+        "--new-window --mode=debug main.rs -- --file=x, true, debug, nil, 5"
+    );
+}
+
+#[test]
+fn test_project_hooks() {
+    let unique_name = "test_project_hooks";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let proof_path = home_dir_path.join("proof.txt");
+    let hook_path = home_dir_path.join("hook.lua");
+    let write_hook = |fail: bool| {
+        fs::write(
+            &hook_path,
+            format!(
+                r#"
+                local proof = io.open("{}", "a")
+                proof:write(PM.action, " ", PM.name, " ", tostring(PM.target), "\n")
+                proof:close()
+                if {fail} then error("veto") end
+                "#,
+                proof_path.to_str().unwrap()
+            ),
+        )
+        .unwrap()
+    };
+    write_hook(false);
+    let lib_path = home_dir_path.join("lib");
+    create_lib("lib", lib_path.to_str().unwrap(), false, false, None, &xdg).unwrap();
+    create_alias_group(
+        "docs",
+        home_dir_path.join("docs").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    define_project_type("app", None, None, None, false, &xdg).unwrap();
+    for hook in ["on_delete", "on_move", "on_add_to_group"] {
+        set_config_value(
+            &format!("project_types.app.{hook}"),
+            &[hook_path.to_str().unwrap()],
+            &xdg,
+        )
+        .unwrap();
+    }
+    create_project(
+        "site",
+        Some("app"),
+        None,
+        Some("lib"),
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    add_to_alias_group("site", "docs", Some("lib"), &xdg).unwrap();
+    assert!(fs::symlink_metadata(home_dir_path.join("docs/site")).is_ok());
+    let moved_lib_path = home_dir_path.join("moved");
+    update_library("lib", None, moved_lib_path.to_str(), &xdg).unwrap();
+    assert!(moved_lib_path.join("site").is_dir());
+    assert_eq!(
+        fs::read_to_string(&proof_path).unwrap(),
+        format!(
+            "add_to_group site docs\nmove site {}\n",
+            moved_lib_path.join("site").to_str().unwrap()
+        )
+    );
+
+    // a failing hook cancels the operation
+    write_hook(true);
+    match delete_library("lib", &xdg) {
+        Err(DeleteLibraryError::HookRuntime(err)) => {
+            assert_eq!(err.hook, "on_delete");
+            assert_eq!(err.path, hook_path.to_str().unwrap());
+            assert!(err.message.contains("veto"), "{}", err.message);
+        }
+        result => panic!("Expected a hook error, got {result:?}"),
+    }
+    assert!(moved_lib_path.join("site").is_dir());
+    assert!(get_libraries(&xdg).unwrap().contains_key("lib"));
+
+    write_hook(false);
+    delete_library("lib", &xdg).unwrap();
+    assert!(!moved_lib_path.exists());
+    assert!(fs::read_to_string(&proof_path)
+        .unwrap()
+        .ends_with("delete site nil\ndelete site nil\n"));
+}
+
+#[test]
+fn test_project_lifecycle_hooks() {
+    let unique_name = "test_project_lifecycle_hooks";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let proof_path = home_dir_path.join("proof.txt");
+    let hook_path = home_dir_path.join("hook.lua");
+    let write_hook = |fail: bool| {
+        fs::write(
+            &hook_path,
+            format!(
+                r#"
+                local proof = io.open("{}", "a")
+                proof:write(PM.action, " ", PM.name, " ", tostring(PM.target), "\n")
+                proof:close()
+                if {fail} then error("veto") end
+                "#,
+                proof_path.to_str().unwrap()
+            ),
+        )
+        .unwrap()
+    };
+    write_hook(false);
+    let lib_path = home_dir_path.join("lib");
+    let archive_path = home_dir_path.join("archive");
+    for (name, path) in [("lib", &lib_path), ("archive", &archive_path)] {
+        create_lib(name, path.to_str().unwrap(), false, false, None, &xdg).unwrap();
+    }
+    create_alias_group(
+        "docs",
+        home_dir_path.join("docs").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    let opener_path = home_dir_path.join("opener.lua");
+    fs::write(&opener_path, "").unwrap();
+    define_project_type("latex", None, None, opener_path.to_str(), false, &xdg).unwrap();
+    for hook in [
+        "on_rename",
+        "on_move",
+        "on_archive",
+        "on_delete",
+        "on_close",
+    ] {
+        set_config_value(
+            &format!("project_types.latex.{hook}"),
+            &[hook_path.to_str().unwrap()],
+            &xdg,
+        )
+        .unwrap();
+    }
+    create_project(
+        "thesis",
+        Some("latex"),
+        None,
+        Some("lib"),
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    add_to_alias_group("thesis", "docs", Some("lib"), &xdg).unwrap();
+
+    // renaming renames the aliases named after the project
+    update_project("thesis", Some("lib"), Some("paper"), None, &xdg).unwrap();
+    assert!(lib_path.join("paper").is_dir());
+    assert!(!lib_path.join("thesis").exists());
+    assert!(fs::symlink_metadata(home_dir_path.join("docs/thesis")).is_err());
+    assert_eq!(
+        fs::canonicalize(home_dir_path.join("docs/paper")).unwrap(),
+        fs::canonicalize(lib_path.join("paper")).unwrap()
+    );
+    assert_eq!(
+        fs::read_to_string(&proof_path).unwrap(),
+        "rename thesis paper\n"
+    );
+
+    assert!(matches!(
+        archive_project("paper", Some("lib"), &xdg),
+        Err(ArchiveProjectError::LibNotTracked(_))
+    ));
+    set_config_value("archive_lib", &["archive"], &xdg).unwrap();
+
+    // a failing hook cancels the archiving
+    write_hook(true);
+    match archive_project("paper", Some("lib"), &xdg) {
+        Err(ArchiveProjectError::HookRuntime(err)) => {
+            assert_eq!(err.hook, "on_archive");
+            assert!(err.message.contains("veto"), "{}", err.message);
+        }
+        result => panic!("Expected a hook error, got {result:?}"),
+    }
+    assert!(lib_path.join("paper").is_dir());
+
+    write_hook(false);
+    archive_project("paper", Some("lib"), &xdg).unwrap();
+    assert!(archive_path.join("paper").is_dir());
+    assert_eq!(
+        fs::canonicalize(home_dir_path.join("docs/paper")).unwrap(),
+        fs::canonicalize(archive_path.join("paper")).unwrap()
+    );
+    assert!(fs::read_to_string(&proof_path).unwrap().ends_with(&format!(
+        "archive paper {}\n",
+        archive_path.join("paper").to_str().unwrap()
+    )));
+
+    // moving to another library runs on_move
+    update_project("paper", Some("archive"), None, Some("lib"), &xdg).unwrap();
+    assert!(lib_path.join("paper").is_dir());
+    assert!(fs::read_to_string(&proof_path).unwrap().ends_with(&format!(
+        "move paper {}\n",
+        lib_path.join("paper").to_str().unwrap()
+    )));
+
+    // on_close runs once the opener exits
+    open_project("paper", Some("lib"), &ScriptArgs::default(), &xdg).unwrap();
+    assert!(fs::read_to_string(&proof_path)
+        .unwrap()
+        .ends_with("close paper nil\n"));
+
+    // a project config that can't be read doesn't skip the hooks
+    let project_config_path = lib_path
+        .join("paper")
+        .join(ProjectConfig::PROJECT_ROOT_REL_PATH);
+    let project_config = fs::read_to_string(&project_config_path).unwrap();
+    fs::write(&project_config_path, "project_type = ").unwrap();
+    assert!(matches!(
+        delete_project("paper", Some("lib"), &xdg),
+        Err(DeleteProjectError::ProjectConfigError(_))
+    ));
+    fs::write(&project_config_path, project_config).unwrap();
+
+    write_hook(true);
+    match delete_project("paper", Some("lib"), &xdg) {
+        Err(DeleteProjectError::HookRuntime(err)) => assert_eq!(err.hook, "on_delete"),
+        result => panic!("Expected a hook error, got {result:?}"),
+    }
+    assert!(lib_path.join("paper").is_dir());
+
+    write_hook(false);
+    delete_project("paper", Some("lib"), &xdg).unwrap();
+    assert!(!lib_path.join("paper").exists());
+    assert!(fs::symlink_metadata(home_dir_path.join("docs/paper")).is_err());
+    assert!(fs::read_to_string(&proof_path)
+        .unwrap()
+        .ends_with("delete paper nil\n"));
+}
+
+#[test]
+fn test_builder_steps() {
+    let unique_name = "test_builder_steps";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let builders_path = home_dir_path.join(".local/share/project_manager/builders");
+    fs::create_dir_all(&builders_path).unwrap();
+    for (step, script) in [
+        ("git.lua", r#"steps = {"git"}"#),
+        ("venv.lua", r#"table.insert(steps, "venv")"#),
+        (
+            "license.lua",
+            r#"
+            table.insert(steps, "license")
+            local proof = io.open(PM.path .. "/steps.txt", "w")
+            proof:write(table.concat(steps, " "))
+            proof:close()
+            "#,
+        ),
+        ("fail.lua", r#"error("step failed")"#),
+    ] {
+        fs::write(builders_path.join(step), script).unwrap();
+    }
+    define_project_type(
+        "python",
+        None,
+        Some(
+            ["git.lua", "venv.lua", "license.lua"]
+                .map(String::from)
+                .to_vec(),
+        ),
+        None,
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "api",
+        Some("python"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    let projects_path = home_dir_path.join(".local/share/project_manager/projects");
+    assert_eq!(
+        fs::read_to_string(projects_path.join("api/steps.txt")).unwrap(),
+        "git venv license"
+    );
+
+    // the steps stop at the first one that fails
+    set_config_value(
+        "project_types.python.builder",
+        &["git.lua", "fail.lua", "license.lua"],
+        &xdg,
+    )
+    .unwrap();
+    match create_project(
+        "cli",
+        Some("python"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    ) {
+        Err(CreateProjectError::BuilderRuntime(err)) => {
+            assert_eq!(err.path, builders_path.join("fail.lua").to_str().unwrap());
+        }
+        result => panic!("Expected a builder error, got {result:?}"),
+    }
+    assert!(!projects_path.join("cli/steps.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_executable_scripts() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let unique_name = "test_executable_scripts";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let data_path = home_dir_path.join(".local/share/project_manager");
+    fs::create_dir_all(data_path.join("builders")).unwrap();
+    fs::create_dir_all(data_path.join("openers")).unwrap();
+    for (script, contents) in [
+        (
+            "builders/build.sh",
+            "#!/bin/sh\necho \"$DONNA_ACTION $DONNA_NAME $DONNA_TYPE\" > env.txt\ncat > context.json\n",
+        ),
+        (
+            "builders/step.lua",
+            r#"
+            local proof = io.open(PM.path .. "/step.txt", "w")
+            proof:write(PM.name)
+            proof:close()
+            "#,
+        ),
+        ("openers/open", "#!/bin/sh\necho \"$@\" > args.txt\n"),
+        ("openers/fail.lua", "#!/bin/sh\nexit 3\n"),
+    ] {
+        let path = data_path.join(script);
+        fs::write(&path, contents).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    // scripts with an extension are executables even without a shebang or the executable bit
+    fs::write(
+        data_path.join("builders/setup.sh"),
+        "echo $DONNA_NAME > setup.txt\n",
+    )
+    .unwrap();
+    fs::write(data_path.join("builders/notes.txt"), "not a script\n").unwrap();
+    define_project_type(
+        "shell",
+        None,
+        Some(
+            ["build.sh", "step.lua", "setup.sh"]
+                .map(String::from)
+                .to_vec(),
+        ),
+        Some("open"),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "tool",
+        Some("shell"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    // executables run in the project directory with the context in env vars and on stdin, next to Lua steps
+    let project_path = data_path.join("projects/tool");
+    assert_eq!(
+        fs::read_to_string(project_path.join("env.txt")).unwrap(),
+        "build tool shell\n"
+    );
+    let context: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(project_path.join("context.json")).unwrap())
+            .unwrap();
+    assert_eq!(context["path"], project_path.to_str().unwrap());
+    assert_eq!(context["type"], "shell");
+    assert_eq!(
+        fs::read_to_string(project_path.join("step.txt")).unwrap(),
+        "tool"
+    );
+    assert_eq!(
+        fs::read_to_string(project_path.join("setup.txt")).unwrap(),
+        "tool\n"
+    );
+
+    // a script that isn't executable and has no known interpreter isn't run as Lua
+    define_project_type(
+        "notes",
+        None,
+        Some(vec!["notes.txt".to_string()]),
+        None,
+        false,
+        &xdg,
+    )
+    .unwrap();
+    match create_project(
+        "notes",
+        Some("notes"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    ) {
+        Err(CreateProjectError::Executable(err)) => {
+            assert!(err.message.contains("isn't executable"));
+        }
+        result => panic!("Expected an executable error, got {result:?}"),
+    }
+
+    let args = ScriptArgs::new(vec!["--new-window".to_string(), "src".to_string()]);
+    open_project("tool", None, &args, &xdg).unwrap();
+    assert_eq!(
+        fs::read_to_string(project_path.join("args.txt")).unwrap(),
+        "--new-window src\n"
+    );
+
+    // an explicit kind overrides the extension and a non-zero exit is an error
+    set_config_value("project_types.shell.opener", &["fail.lua"], &xdg).unwrap();
+    set_config_value("project_types.shell.opener_kind", &["executable"], &xdg).unwrap();
+    assert!(set_config_value("project_types.shell.opener_kind", &["binary"], &xdg).is_err());
+    create_project(
+        "broken",
+        Some("shell"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    match open_project("broken", None, &ScriptArgs::default(), &xdg) {
+        Err(OpenProjectError::Executable(err)) => {
+            assert_eq!(err.code, Some(3));
+            assert!(err.path.ends_with("fail.lua"));
+        }
+        result => panic!("Expected an executable error, got {result:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_and_spawn() {
+    let unique_name = "test_exec_and_spawn";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let builders_path = home_dir_path.join(".local/share/project_manager/builders");
+    fs::create_dir_all(&builders_path).unwrap();
+    fs::write(
+        builders_path.join("builder.lua"),
+        r#"
+        local result = donna.exec{
+            "sh", "-c", "echo $GREETING from $(pwd); echo oops >&2; exit 2", env = {GREETING = "hello"},
+        }
+        local proof = io.open(PM.path .. "/exec.txt", "w")
+        proof:write(result.code .. "|" .. result.stdout .. "|" .. result.stderr)
+        proof:close()
+
+        donna.exec{"mkdir", "my dir"}
+        donna.exec{"touch", "it's; rm -rf .", cwd = "my dir"}
+        local ok, err = pcall(donna.exec, {"donna-test-missing-command"})
+        proof = io.open(PM.path .. "/missing.txt", "w")
+        proof:write(tostring(ok) .. " " .. tostring(err))
+        proof:close()
+        donna.spawn{"touch", "spawned"}
+        "#,
+    )
+    .unwrap();
+    define_project_type(
+        "exec",
+        None,
+        Some(vec!["builder.lua".to_string()]),
+        None,
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "my app",
+        Some("exec"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    // commands run in the project directory without a shell, so spaces and quotes in paths are kept
+    let project_path = home_dir_path.join(".local/share/project_manager/projects/my app");
+    assert_eq!(
+        fs::read_to_string(project_path.join("exec.txt")).unwrap(),
+        format!("2|hello from {}\n|oops\n", project_path.display())
+    );
+    assert!(project_path.join("my dir/it's; rm -rf .").exists());
+    let missing = fs::read_to_string(project_path.join("missing.txt")).unwrap();
+    assert!(missing.starts_with("false"));
+    assert!(missing.contains("Failed to run donna-test-missing-command"));
+
+    // spawned processes aren't waited for
+    let spawned = project_path.join("spawned");
+    for _ in 0..50 {
+        if spawned.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert!(spawned.exists());
+}
+
+#[test]
+fn test_prompts() {
+    let unique_name = "test_prompts";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let builders_path = home_dir_path.join(".local/share/project_manager/builders");
+    fs::create_dir_all(&builders_path).unwrap();
+    fs::write(
+        builders_path.join("builder.lua"),
+        r#"
+        local answers = {
+            donna.prompt("Description", "none"),
+            donna.prompt("License?", nil, "license"),
+            tostring(donna.confirm("Initialize git?", false, "git")),
+            donna.select("Framework", {"flask", "django"}, "flask", "framework"),
+            tostring(donna.confirm("Continue?")),
+        }
+        local proof = io.open(PM.path .. "/answers.txt", "w")
+        proof:write(table.concat(answers, "|"))
+        proof:close()
+        "#,
+    )
+    .unwrap();
+    define_project_type(
+        "python",
+        None,
+        Some(vec!["builder.lua".to_string()]),
+        None,
+        false,
+        &xdg,
+    )
+    .unwrap();
+    let params = |params: &[(&str, &str)]| {
+        params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    // --param values answer questions by name, with --yes the rest take their defaults or are confirmed
+    let args = ScriptArgs::default()
+        .with_params(params(&[
+            ("license", "MIT"),
+            ("git", "no"),
+            ("framework", "django"),
+        ]))
+        .with_assume_yes(true);
+    create_project("api", Some("python"), None, None, false, None, &args, &xdg).unwrap();
+    let projects_path = home_dir_path.join(".local/share/project_manager/projects");
+    assert_eq!(
+        fs::read_to_string(projects_path.join("api/answers.txt")).unwrap(),
+        "none|MIT|false|django|true"
+    );
+
+    // a question without a default fails under --yes
+    let args = ScriptArgs::default().with_assume_yes(true);
+    match create_project("web", Some("python"), None, None, false, None, &args, &xdg) {
+        Err(CreateProjectError::BuilderRuntime(err)) => {
+            assert!(err.message.contains(
+                "License? has no default and --yes was given, answer it with --param 'license=VALUE'"
+            ));
+        }
+        result => panic!("Expected a builder error, got {result:?}"),
+    }
+
+    // answers to selections must be one of the options
+    let args = ScriptArgs::default()
+        .with_params(params(&[("license", "MIT"), ("framework", "rails")]))
+        .with_assume_yes(true);
+    match create_project("cli", Some("python"), None, None, false, None, &args, &xdg) {
+        Err(CreateProjectError::BuilderRuntime(err)) => {
+            assert!(err
+                .message
+                .contains("framework must be one of flask, django, got rails"));
+        }
+        result => panic!("Expected a builder error, got {result:?}"),
+    }
+
+    // projects created by a builder keep the answers and --yes, and get the params it passes
+    fs::write(
+        builders_path.join("app.lua"),
+        r#"donna.create_project{name = PM.name .. "-docs", type = "docs", params = {theme = "dark"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        builders_path.join("docs.lua"),
+        r#"
+        local proof = io.open(PM.path .. "/answers.txt", "w")
+        proof:write(donna.prompt("Theme", nil, "theme") .. "|" .. donna.prompt("License?", nil, "license")
+            .. "|" .. tostring(donna.confirm("Publish?")))
+        proof:close()
+        "#,
+    )
+    .unwrap();
+    for (name, builder) in [("app", "app.lua"), ("docs", "docs.lua")] {
+        define_project_type(
+            name,
+            None,
+            Some(vec![builder.to_string()]),
+            None,
+            false,
+            &xdg,
+        )
+        .unwrap();
+    }
+    let args = ScriptArgs::default()
+        .with_params(params(&[("license", "MIT")]))
+        .with_assume_yes(true);
+    create_project("site", Some("app"), None, None, false, None, &args, &xdg).unwrap();
+    assert_eq!(
+        fs::read_to_string(projects_path.join("site-docs/answers.txt")).unwrap(),
+        "dark|MIT|true"
+    );
+}
@@ -0,0 +1,721 @@
+use donna::{
+    create_alias_group, create_lib, create_profile, create_project, define_project_type,
+    errors::{
+        ConfigError, ConfigValueError, RestoreError, UntrackProjectTypeError, ValidateConfigError,
+    },
+    export_state, get_builders_path, get_config_keys, get_config_value, get_config_values,
+    get_libraries, get_profiles, get_project_types, resolve_profile, restore_state,
+    set_config_value, set_ignore_patterns, switch_profile, unset_config_value,
+    untrack_project_type, validate_config, Builder, Config, ConfigIssueKind, ProjectRestoreStatus,
+    ScriptArgs, XDG,
+};
+use std::fs;
+use std::path::PathBuf;
+
+mod utils;
+use utils::{gen_test_home_path, setup_home};
+
+#[test]
+fn test_validate_config() {
+    let unique_name = "test_validate_config";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    create_alias_group(
+        "group",
+        home_dir_path.join("group").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    assert!(
+        validate_config(&xdg).unwrap().is_empty(),
+        "A config written by donna should be valid"
+    );
+
+    let config_path = Config::get_path(&xdg);
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        format!(
+            "colour = \"red\"\ndefault_lib = \"missing\"\n{config}\n[project_types.rust]\nbulider = \"typo\"\nbuilder = \"/does/not/exist.lua\"\ndefault_alias_groups = [\"group\", \"missing\"]\n"
+        ),
+    )
+    .unwrap();
+
+    let issues = validate_config(&xdg).unwrap();
+    let kinds: Vec<_> = issues.iter().map(|issue| issue.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ConfigIssueKind::UnknownKey,
+            ConfigIssueKind::DefaultLibNotTracked {
+                lib: "missing".to_string()
+            },
+            ConfigIssueKind::UnknownKey,
+            ConfigIssueKind::ScriptNotFound {
+                path: PathBuf::from("/does/not/exist.lua")
+            },
+            ConfigIssueKind::UnknownAliasGroup {
+                alias_group: "missing".to_string()
+            },
+        ],
+        "Issues should be reported in the order they appear in the file"
+    );
+    assert_eq!(issues[0].key, vec!["colour"]);
+    assert_eq!(issues[0].location, Some((1, 1)));
+    assert_eq!(issues[2].key, vec!["project_types", "rust", "bulider"]);
+
+    fs::write(&config_path, "default_lib = \n").unwrap();
+    match validate_config(&xdg) {
+        Err(ValidateConfigError::ConfigError(ConfigError::TomlLoad(path, error))) => {
+            assert_eq!(path, config_path);
+            assert!(
+                error.to_string().contains("line 1"),
+                "The error should point to the line: {error}"
+            );
+        }
+        other => panic!("Expected a parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_concurrent_config_changes() {
+    let unique_name = "test_concurrent_config_changes";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            let xdg = &xdg;
+            let group_path = home_dir_path.join(format!("group{i}"));
+            scope.spawn(move || {
+                create_alias_group(
+                    &format!("group{i}"),
+                    group_path.to_str().unwrap(),
+                    false,
+                    xdg,
+                )
+                .unwrap();
+            });
+        }
+    });
+
+    let config = Config::load(None, &xdg).unwrap();
+    assert_eq!(
+        config.get_alias_groups().unwrap().len(),
+        8,
+        "No alias group should be lost when they are created at the same time"
+    );
+    let leftover_files: Vec<_> = fs::read_dir(Config::get_path(&xdg).parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(
+        leftover_files.is_empty(),
+        "Temporary files were left behind"
+    );
+}
+
+#[test]
+fn test_profiles() {
+    let unique_name = "test_profiles";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    create_profile("work", false, &xdg).unwrap();
+    create_profile("personal", true, &xdg).unwrap();
+    assert!(create_profile("work", false, &xdg).is_err());
+    assert!(create_profile("default", false, &xdg).is_err());
+    assert_eq!(
+        get_profiles(&xdg).unwrap(),
+        vec!["default", "personal", "work"]
+    );
+
+    let work_xdg = xdg.clone().with_profile(Some("work"));
+    create_lib(
+        "work_lib",
+        home_dir_path.join("work_lib").to_str().unwrap(),
+        true,
+        false,
+        None,
+        &work_xdg,
+    )
+    .unwrap();
+    assert!(get_libraries(&work_xdg).unwrap().contains_key("work_lib"));
+    assert!(
+        !get_libraries(&xdg).unwrap().contains_key("work_lib"),
+        "Libraries should not leak between profiles"
+    );
+    assert_ne!(
+        get_libraries(&work_xdg).unwrap()["default"],
+        get_libraries(&xdg).unwrap()["default"],
+        "Each profile should have its own default library"
+    );
+
+    let personal_xdg = xdg.clone().with_profile(Some("personal"));
+    assert_eq!(
+        get_builders_path(&work_xdg).unwrap(),
+        get_builders_path(&xdg).unwrap(),
+        "Builders should be shared by default"
+    );
+    assert_ne!(
+        get_builders_path(&personal_xdg).unwrap(),
+        get_builders_path(&xdg).unwrap(),
+        "Builders should be separate when asked for"
+    );
+
+    assert_eq!(resolve_profile(None, &xdg).unwrap(), None);
+    switch_profile("work", &xdg).unwrap();
+    assert_eq!(
+        resolve_profile(None, &xdg).unwrap(),
+        Some("work".to_string())
+    );
+    assert_eq!(
+        resolve_profile(Some("personal"), &xdg).unwrap(),
+        Some("personal".to_string())
+    );
+    assert_eq!(resolve_profile(Some("default"), &xdg).unwrap(), None);
+    assert!(resolve_profile(Some("missing"), &xdg).is_err());
+    switch_profile("default", &xdg).unwrap();
+    assert_eq!(resolve_profile(None, &xdg).unwrap(), None);
+}
+
+#[test]
+fn test_layered_config() {
+    let unique_name = "test_layered_config";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let config_path = Config::get_path(&xdg);
+    let config_dir = config_path.parent().unwrap();
+    let shared_path = config_dir.join("shared.toml");
+    fs::write(
+        &shared_path,
+        "ignore_patterns = [\"shared*\"]\nunknown_key = true\n\n[project_types.rust]\ndefault_alias_groups = []\n",
+    )
+    .unwrap();
+    let drop_in_path = config_dir.join("config.d/10-team.toml");
+    fs::create_dir_all(drop_in_path.parent().unwrap()).unwrap();
+    fs::write(&drop_in_path, "ignore_patterns = [\"team*\"]\n").unwrap();
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        format!("include = [\"shared.toml\"]\n{config}"),
+    )
+    .unwrap();
+
+    assert!(get_project_types(&xdg).unwrap().contains_key("rust"));
+    let values = get_config_values(&xdg).unwrap();
+    let origin_of = |key: &[&str]| {
+        values
+            .iter()
+            .find(|value| value.key == key)
+            .unwrap()
+            .origin
+            .clone()
+    };
+    assert_eq!(
+        origin_of(&["ignore_patterns"]),
+        Some(drop_in_path.clone()),
+        "config.d should be merged over included files"
+    );
+    assert_eq!(
+        origin_of(&["project_types", "rust", "default_alias_groups"]),
+        Some(shared_path.clone())
+    );
+    assert_eq!(origin_of(&["include"]), Some(config_path.clone()));
+    assert_eq!(origin_of(&["builders_dir"]), None);
+
+    create_lib(
+        "lib",
+        home_dir_path.join("lib").to_str().unwrap(),
+        true,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
+    let saved = fs::read_to_string(&config_path).unwrap();
+    assert!(saved.contains("[library_paths]"));
+    assert!(
+        !saved.contains("project_types") && !saved.contains("ignore_patterns"),
+        "Shared values should not be copied into the user's config: {saved}"
+    );
+    assert!(get_project_types(&xdg).unwrap().contains_key("rust"));
+
+    // the user's own values win over the shared ones
+    set_ignore_patterns(Some(vec!["mine*".to_string()]), &xdg).unwrap();
+    assert_eq!(
+        get_config_values(&xdg)
+            .unwrap()
+            .into_iter()
+            .find(|value| value.key == ["ignore_patterns"])
+            .unwrap()
+            .origin,
+        Some(config_path.clone())
+    );
+
+    // removing the user's own value brings back the shared one, shared values can't be removed
+    unset_config_value("ignore_patterns", &xdg).unwrap();
+    assert_eq!(
+        get_config_values(&xdg)
+            .unwrap()
+            .into_iter()
+            .find(|value| value.key == ["ignore_patterns"])
+            .unwrap()
+            .origin,
+        Some(drop_in_path.clone())
+    );
+    let saved = fs::read_to_string(&config_path).unwrap();
+    match untrack_project_type("rust", &xdg) {
+        Err(UntrackProjectTypeError::ConfigError(ConfigError::InheritedKey(key, path))) => {
+            assert_eq!(key, "project_types.rust");
+            assert_eq!(path, shared_path);
+        }
+        result => panic!("Expected an inherited key error, got {result:?}"),
+    }
+    assert!(matches!(
+        unset_config_value("ignore_patterns", &xdg),
+        Err(ConfigValueError::ConfigError(ConfigError::InheritedKey(
+            _,
+            _
+        )))
+    ));
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), saved);
+    assert!(get_project_types(&xdg).unwrap().contains_key("rust"));
+}
+
+#[test]
+fn test_config_get_set_unset() {
+    let unique_name = "test_config_get_set_unset";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let opener_path = home_dir_path.join("opener.lua");
+    set_config_value("config_opener", &[opener_path.to_str().unwrap()], &xdg).unwrap();
+    assert_eq!(
+        get_config_value("config_opener", &xdg).unwrap(),
+        Some(toml::Value::String(
+            opener_path.to_str().unwrap().to_string()
+        ))
+    );
+    unset_config_value("config_opener", &xdg).unwrap();
+    assert_eq!(get_config_value("config_opener", &xdg).unwrap(), None);
+
+    set_config_value("relative_links", &["true"], &xdg).unwrap();
+    assert_eq!(
+        get_config_value("relative_links", &xdg).unwrap(),
+        Some(toml::Value::Boolean(true))
+    );
+    assert!(matches!(
+        set_config_value("relative_links", &["yes"], &xdg),
+        Err(ConfigValueError::InvalidConfigValue(_))
+    ));
+    set_config_value("ignore_patterns", &["tmp*", "old*"], &xdg).unwrap();
+    assert_eq!(
+        get_config_value("ignore_patterns", &xdg).unwrap(),
+        Some(toml::Value::Array(vec![
+            toml::Value::String("tmp*".to_string()),
+            toml::Value::String("old*".to_string())
+        ]))
+    );
+
+    assert!(matches!(
+        set_config_value("colour", &["red"], &xdg),
+        Err(ConfigValueError::InvalidConfigKey(_))
+    ));
+    assert!(
+        matches!(
+            set_config_value("default_lib", &["missing"], &xdg),
+            Err(ConfigValueError::InvalidConfigValue(_))
+        ),
+        "The default library must be tracked"
+    );
+    assert!(
+        matches!(
+            set_config_value("alias_groups.new.relative_links", &["true"], &xdg),
+            Err(ConfigValueError::InvalidConfigValue(_))
+        ),
+        "An alias group needs a path"
+    );
+
+    create_alias_group(
+        "group",
+        home_dir_path.join("group").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    assert!(get_config_keys(&xdg)
+        .unwrap()
+        .contains(&"alias_groups.group.relative_links".to_string()));
+    set_config_value("alias_groups.group.relative_links", &["true"], &xdg).unwrap();
+    assert!(Config::load(None, &xdg)
+        .unwrap()
+        .uses_relative_links("group"));
+
+    // parts with dots are quoted like in TOML
+    let dotted_lib_path = home_dir_path.join("my.lib");
+    set_config_value(
+        "library_paths.\"my.lib\"",
+        &[dotted_lib_path.to_str().unwrap()],
+        &xdg,
+    )
+    .unwrap();
+    assert_eq!(
+        get_libraries(&xdg).unwrap().get("my.lib"),
+        Some(&dotted_lib_path.to_str().unwrap().to_string())
+    );
+    assert_eq!(
+        get_config_value("library_paths.'my.lib'", &xdg).unwrap(),
+        Some(toml::Value::String(
+            dotted_lib_path.to_str().unwrap().to_string()
+        ))
+    );
+    assert!(get_config_keys(&xdg)
+        .unwrap()
+        .contains(&"library_paths.\"my.lib\"".to_string()));
+    unset_config_value("library_paths.\"my.lib\"", &xdg).unwrap();
+    assert!(!get_libraries(&xdg).unwrap().contains_key("my.lib"));
+    assert!(matches!(
+        get_config_value("library_paths.\"my.lib", &xdg),
+        Err(ConfigValueError::InvalidConfigKey(_))
+    ));
+}
+
+#[test]
+fn test_portable_paths() {
+    let unique_name = "test_portable_paths";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let config_path = Config::get_path(&xdg);
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        format!(
+            "{config}\n[variables]\nwork = \"$HOME/work\"\n\n[alias_groups.group]\npath = \"${{work}}/group\"\n"
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(
+        get_config_value("alias_groups.group.path", &xdg).unwrap(),
+        Some(toml::Value::String(
+            home_dir_path
+                .join("work/group")
+                .to_str()
+                .unwrap()
+                .to_string()
+        ))
+    );
+
+    set_config_value("library_paths.other", &["~/other"], &xdg).unwrap();
+    assert_eq!(
+        get_config_value("library_paths.other", &xdg).unwrap(),
+        Some(toml::Value::String(
+            home_dir_path.join("other").to_str().unwrap().to_string()
+        ))
+    );
+    fs::create_dir_all(home_dir_path.join("steps")).unwrap();
+    fs::write(home_dir_path.join("steps/git.lua"), "").unwrap();
+    fs::write(home_dir_path.join("steps/venv.lua"), "").unwrap();
+    define_project_type("python", None, None, None, false, &xdg).unwrap();
+    set_config_value(
+        "project_types.python.builder",
+        &["~/steps/git.lua", "~/steps/venv.lua"],
+        &xdg,
+    )
+    .unwrap();
+    set_config_value("relative_links", &["true"], &xdg).unwrap();
+    let raw = fs::read_to_string(&config_path).unwrap();
+    assert!(raw.contains("other = \"~/other\""), "{raw}");
+    assert!(
+        raw.contains("builder = [\"~/steps/git.lua\", \"~/steps/venv.lua\"]"),
+        "{raw}"
+    );
+    assert!(raw.contains("path = \"${work}/group\""), "{raw}");
+
+    // paths donna writes itself are read back as they are, not as variables
+    let weird_path = home_dir_path.join("a$b");
+    create_lib(
+        "weird",
+        weird_path.to_str().unwrap(),
+        false,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
+    assert!(fs::read_to_string(&config_path)
+        .unwrap()
+        .contains("/a$$b\""));
+    assert_eq!(
+        get_libraries(&xdg).unwrap().get("weird"),
+        Some(&weird_path.to_str().unwrap().to_string())
+    );
+
+    assert!(matches!(
+        set_config_value("library_paths.missing", &["$NOT_A_DONNA_VAR/lib"], &xdg),
+        Err(ConfigValueError::ConfigError(
+            ConfigError::PathVarNotDefined(_)
+        ))
+    ));
+    fs::write(
+        &config_path,
+        raw.replace("${work}/group", "${missing}/group"),
+    )
+    .unwrap();
+    assert!(matches!(
+        get_config_value("alias_groups.group.path", &xdg),
+        Err(ConfigValueError::ConfigError(
+            ConfigError::PathVarNotDefined(_)
+        ))
+    ));
+}
+
+#[test]
+fn test_export_restore() {
+    let unique_name = "test_export_restore";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    create_lib(
+        "lib",
+        home_dir_path.join("lib").to_str().unwrap(),
+        true,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
+    create_alias_group(
+        "group",
+        home_dir_path.join("group").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    let builders_path = PathBuf::from(get_builders_path(&xdg).unwrap());
+    fs::create_dir_all(&builders_path).unwrap();
+    fs::write(builders_path.join("rust.lua"), "-- build").unwrap();
+    // a compiled builder and an executable script without a shebang
+    fs::write(
+        builders_path.join("compiled"),
+        [0x7f, b'E', b'L', b'F', 0xff, 0x00],
+    )
+    .unwrap();
+    fs::write(builders_path.join("setup.sh"), "echo setup").unwrap();
+    #[cfg(unix)]
+    for (name, mode) in [("compiled", 0o755), ("setup.sh", 0o744)] {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(builders_path.join(name), fs::Permissions::from_mode(mode)).unwrap();
+    }
+    define_project_type(
+        "rust",
+        Some(vec!["group".to_string()]),
+        Some(vec![builders_path
+            .join("rust.lua")
+            .to_str()
+            .unwrap()
+            .to_string()]),
+        None,
+        false,
+        &xdg,
+    )
+    .unwrap();
+
+    let repo_path = home_dir_path.join("repo");
+    fs::create_dir_all(&repo_path).unwrap();
+    fs::write(repo_path.join("README.md"), "# Repo").unwrap();
+    for args in [
+        vec!["init"],
+        vec!["add", "."],
+        vec!["commit", "-m", "Initial commit"],
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&repo_path)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .unwrap();
+    }
+    create_project(
+        "cloned",
+        Some("rust"),
+        None,
+        None,
+        false,
+        Some(repo_path.to_str().unwrap()),
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "local",
+        Some("rust"),
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    let export_path = home_dir_path.join("export.toml");
+    export_state(export_path.to_str().unwrap(), &xdg).unwrap();
+    let export = fs::read_to_string(&export_path).unwrap();
+    assert!(export.contains("\"~/lib\""), "{export}");
+
+    // restore on a machine with another home directory
+    let new_name = "test_export_restore_new";
+    let new_config_home_name = new_name.to_string() + "_config";
+    let new_data_home_name = new_name.to_string() + "_data";
+    let new_xdg = XDG::new(
+        Some(new_name),
+        Some(&new_config_home_name),
+        Some(&new_data_home_name),
+    );
+    let _new_cleanup = setup_home(new_name, &new_xdg);
+
+    let new_home_dir_path = gen_test_home_path(new_name);
+    let restored = restore_state(export_path.to_str().unwrap(), false, &new_xdg).unwrap();
+    assert_eq!(
+        restored,
+        vec![
+            ("cloned".to_string(), ProjectRestoreStatus::Cloned),
+            ("local".to_string(), ProjectRestoreStatus::NoRemote),
+        ]
+    );
+    assert_eq!(
+        get_libraries(&new_xdg).unwrap().get("lib"),
+        Some(&new_home_dir_path.join("lib").to_str().unwrap().to_string())
+    );
+    assert!(new_home_dir_path.join("lib/cloned/README.md").exists());
+    assert!(fs::symlink_metadata(new_home_dir_path.join("group/cloned")).is_ok());
+    let new_builders_path = PathBuf::from(get_builders_path(&new_xdg).unwrap());
+    assert_eq!(
+        fs::read_to_string(new_builders_path.join("rust.lua")).unwrap(),
+        "-- build"
+    );
+    assert_eq!(
+        fs::read(new_builders_path.join("compiled")).unwrap(),
+        [0x7f, b'E', b'L', b'F', 0xff, 0x00]
+    );
+    assert_eq!(
+        fs::read_to_string(new_builders_path.join("setup.sh")).unwrap(),
+        "echo setup"
+    );
+    #[cfg(unix)]
+    for (name, mode) in [
+        ("compiled", 0o755),
+        ("setup.sh", 0o744),
+        ("rust.lua", 0o644),
+    ] {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata = fs::metadata(new_builders_path.join(name)).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777 & mode, mode, "{name}");
+        assert_eq!(
+            metadata.permissions().mode() & 0o111,
+            mode & 0o111,
+            "{name}"
+        );
+    }
+    assert_eq!(
+        get_project_types(&new_xdg).unwrap()["rust"].builder,
+        Some(Builder::Script(
+            new_builders_path
+                .join("rust.lua")
+                .to_str()
+                .unwrap()
+                .to_string()
+        ))
+    );
+
+    assert!(matches!(
+        restore_state(export_path.to_str().unwrap(), false, &new_xdg),
+        Err(RestoreError::AlreadySetUp(_))
+    ));
+
+    // --force keeps a backup of the replaced config
+    let config_path = Config::get_path(&new_xdg);
+    let replaced_config = fs::read_to_string(&config_path).unwrap();
+    restore_state(export_path.to_str().unwrap(), true, &new_xdg).unwrap();
+    assert_eq!(
+        fs::read_to_string(config_path.with_file_name("config.toml.bak")).unwrap(),
+        replaced_config
+    );
+
+    // project names and libraries that leave their directory are rejected before anything is written
+    for (from, to) in [
+        ("name = \"local\"", "name = \"../escaped\""),
+        ("library = \"lib\"", "library = \"../lib\""),
+        ("name = \"local\"", "name = \"\""),
+    ] {
+        let bad_export_path = home_dir_path.join("bad_export.toml");
+        assert!(export.contains(from), "{export}");
+        fs::write(&bad_export_path, export.replacen(from, to, 1)).unwrap();
+        fs::write(&config_path, "").unwrap();
+        assert!(matches!(
+            restore_state(bad_export_path.to_str().unwrap(), true, &new_xdg),
+            Err(RestoreError::InvalidExport(_))
+        ));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "");
+    }
+    assert!(!new_home_dir_path.join("escaped").exists());
+}
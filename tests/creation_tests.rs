@@ -1,13 +1,18 @@
 use donna::{
-    create_alias_group, create_lib, create_project, define_project_type, Config, ProjectConfig, XDG,
+    create_alias_group, create_lib, create_project, define_project_type, doctor,
+    errors::{ConfigError, CreateAliasGroupError, CreateLibError, GetLibsError},
+    get_builders_path, get_libraries, get_library_dirs, get_offline_libraries, get_openers_path,
+    get_projects, init, set_ignore_patterns, set_library_defaults, untrack_project_type, Builder,
+    Config, HealthIssue, LibraryDefaults, ProjectConfig, ScriptArgs, EXAMPLE_SCRIPT_NAME, XDG,
 };
 mod utils;
 use utils::{
     gen_test_alias_groups_path, gen_test_config_home_path, gen_test_data_home_path,
-    gen_test_home_path, setup_home,
+    gen_test_home_path, set_home_env, setup_home, TestDir,
 };
 
 use rand::prelude::*;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 #[test]
@@ -47,7 +52,17 @@ fn test_create_project_no_alias_no_type() {
     );
     let _cleanup = setup_home(unique_name, &xdg);
 
-    create_project("test-proj", None, None, None, false, None, &xdg).unwrap();
+    create_project(
+        "test-proj",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
 
     assert!(gen_test_data_home_path(unique_name)
         .join("project_manager/projects/test-proj")
@@ -110,6 +125,7 @@ fn test_create_project_with_alias_and_lib() {
             .unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -137,6 +153,7 @@ fn test_create_project_with_alias_and_lib() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -147,6 +164,7 @@ fn test_create_project_with_alias_and_lib() {
         Some("test-external-lib"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -157,6 +175,7 @@ fn test_create_project_with_alias_and_lib() {
         Some("test-external-lib"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -203,6 +222,7 @@ fn test_create_project_with_type() {
         None,
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -247,6 +267,7 @@ fn test_create_projects_with_libs() {
             .unwrap(),
         true,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -259,6 +280,7 @@ fn test_create_projects_with_libs() {
             .unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -271,11 +293,22 @@ fn test_create_projects_with_libs() {
             .unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
 
-    create_project("default-proj", None, None, None, false, None, &xdg).unwrap();
+    create_project(
+        "default-proj",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
     create_project(
         "lib1-proj",
         None,
@@ -283,6 +316,7 @@ fn test_create_projects_with_libs() {
         Some("test-non-default-lib1"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -293,6 +327,7 @@ fn test_create_projects_with_libs() {
         Some("test-non-default-lib2"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -305,11 +340,22 @@ fn test_create_projects_with_libs() {
             .unwrap(),
         true,
         false,
+        None,
         &xdg,
     )
     .unwrap();
 
-    create_project("default-proj-2", None, None, None, false, None, &xdg).unwrap();
+    create_project(
+        "default-proj-2",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
     create_project(
         "old-default-proj",
         None,
@@ -317,6 +363,7 @@ fn test_create_projects_with_libs() {
         Some("test-default-lib"),
         false,
         None,
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -357,6 +404,7 @@ fn test_create_many_projects_with_type_and_alias_and_lib() {
         home_path.join("lib1").to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -365,6 +413,7 @@ fn test_create_many_projects_with_type_and_alias_and_lib() {
         home_path.join("lib2").to_str().unwrap(),
         false,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -373,6 +422,7 @@ fn test_create_many_projects_with_type_and_alias_and_lib() {
         home_path.join("default").to_str().unwrap(),
         true,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -466,6 +516,7 @@ fn test_create_many_projects_with_type_and_alias_and_lib() {
             lib,
             false,
             None,
+            &ScriptArgs::default(),
             &xdg,
         )
         .unwrap();
@@ -592,6 +643,7 @@ fn test_relative_paths_are_handled_properly() {
         test_home.join("lib").to_str().unwrap(),
         true,
         false,
+        None,
         &xdg,
     )
     .unwrap();
@@ -608,6 +660,76 @@ fn test_relative_paths_are_handled_properly() {
     assert!(PathBuf::from(config.get_lib_path(Some("lib")).unwrap()).is_absolute());
 }
 
+#[test]
+fn test_init() {
+    let unique_name = "test_init";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    set_home_env(unique_name, &xdg);
+    let home_dir_path = gen_test_home_path(unique_name);
+    let _cleanup = TestDir::new(home_dir_path.to_str().unwrap());
+
+    assert!(matches!(
+        get_libraries(&xdg),
+        Err(GetLibsError::ConfigError(ConfigError::NotInitialized(_)))
+    ));
+    // commands that change the config report it too, without creating anything
+    assert!(matches!(
+        create_alias_group(
+            "group",
+            home_dir_path.join("group").to_str().unwrap(),
+            false,
+            &xdg
+        ),
+        Err(CreateAliasGroupError::ConfigError(
+            ConfigError::NotInitialized(_)
+        ))
+    ));
+    assert!(!home_dir_path.join("group").exists());
+    assert!(!Config::get_path(&xdg).exists());
+
+    fs::create_dir_all(home_dir_path.join("existing")).unwrap();
+    let lib_path = home_dir_path.join("existing");
+    let group_path = home_dir_path.join("group");
+    init(
+        &[("existing", lib_path.to_str().unwrap())],
+        &[("group", group_path.to_str().unwrap())],
+        true,
+        &xdg,
+    )
+    .unwrap();
+    assert_eq!(
+        get_libraries(&xdg).unwrap().get("existing"),
+        Some(&lib_path.to_str().unwrap().to_string())
+    );
+    assert!(group_path.is_dir());
+    for dir in [
+        get_builders_path(&xdg).unwrap(),
+        get_openers_path(&xdg).unwrap(),
+    ] {
+        assert!(Path::new(&dir).join(EXAMPLE_SCRIPT_NAME).is_file());
+    }
+
+    // running it again keeps what is there
+    fs::write(
+        Path::new(&get_builders_path(&xdg).unwrap()).join(EXAMPLE_SCRIPT_NAME),
+        "-- edited",
+    )
+    .unwrap();
+    init(&[], &[], true, &xdg).unwrap();
+    assert_eq!(
+        fs::read_to_string(Path::new(&get_builders_path(&xdg).unwrap()).join(EXAMPLE_SCRIPT_NAME))
+            .unwrap(),
+        "-- edited"
+    );
+    assert!(get_libraries(&xdg).unwrap().contains_key("existing"));
+}
+
 #[test]
 fn test_create_project_from_git() {
     let unique_name = "test_create_project_from_git";
@@ -660,6 +782,7 @@ fn test_create_project_from_git() {
         None,
         false,
         Some(git_repo_path.to_str().unwrap()),
+        &ScriptArgs::default(),
         &xdg,
     )
     .unwrap();
@@ -690,3 +813,295 @@ fn test_create_project_from_git() {
     assert!(pm_config.as_ref().unwrap().project_type.is_none());
     assert!(pm_config.as_ref().unwrap().builder.is_none());
 }
+
+#[test]
+fn test_create_project_with_library_defaults() {
+    let unique_name = "test_create_project_with_library_defaults";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let alias_group_path = gen_test_alias_groups_path(unique_name);
+    create_alias_group(
+        "code",
+        alias_group_path.join("code").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_alias_group(
+        "current-term",
+        alias_group_path.join("current-term").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    define_project_type(
+        "school",
+        Some(vec!["code".to_string()]),
+        None,
+        Some("school_opener.lua"),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    let openers_path = PathBuf::from(get_openers_path(&xdg).unwrap());
+    fs::create_dir_all(&openers_path).unwrap();
+    fs::write(openers_path.join("term_opener.lua"), "").unwrap();
+
+    // nothing is created if the defaults are invalid
+    for result in [
+        create_lib(
+            "school",
+            home_dir_path.join("school").to_str().unwrap(),
+            false,
+            false,
+            Some(&LibraryDefaults {
+                project_type: Some("missing".to_string()),
+                ..Default::default()
+            }),
+            &xdg,
+        ),
+        create_lib(
+            "school",
+            home_dir_path.join("school").to_str().unwrap(),
+            false,
+            false,
+            Some(&LibraryDefaults {
+                default_alias_groups: Some(vec!["missing".to_string()]),
+                ..Default::default()
+            }),
+            &xdg,
+        ),
+        create_lib(
+            "school",
+            home_dir_path.join("school").to_str().unwrap(),
+            false,
+            false,
+            Some(&LibraryDefaults {
+                builder: Some(Builder::new(vec!["missing.lua".to_string()])),
+                ..Default::default()
+            }),
+            &xdg,
+        ),
+    ] {
+        assert!(result.is_err());
+        assert!(!home_dir_path.join("school").exists());
+        assert!(!get_libraries(&xdg).unwrap().contains_key("school"));
+    }
+    assert!(matches!(
+        create_lib(
+            "school",
+            home_dir_path.join("school").to_str().unwrap(),
+            false,
+            false,
+            Some(&LibraryDefaults {
+                opener: Some("missing.lua".to_string()),
+                ..Default::default()
+            }),
+            &xdg,
+        ),
+        Err(CreateLibError::OpenerPathNotFound(_))
+    ));
+    create_lib(
+        "school",
+        home_dir_path.join("school").to_str().unwrap(),
+        false,
+        false,
+        Some(&LibraryDefaults {
+            project_type: Some("school".to_string()),
+            ..Default::default()
+        }),
+        &xdg,
+    )
+    .unwrap();
+
+    assert!(set_library_defaults("school", Some("missing"), None, None, None, &xdg).is_err());
+    assert!(set_library_defaults("school", None, None, None, Some("missing.lua"), &xdg).is_err());
+    assert!(set_library_defaults("missing", Some("school"), None, None, None, &xdg).is_err());
+    set_library_defaults(
+        "school",
+        Some("school"),
+        Some(vec!["current-term".to_string()]),
+        None,
+        Some("term_opener.lua"),
+        &xdg,
+    )
+    .unwrap();
+
+    create_project(
+        "hw4",
+        None,
+        None,
+        Some("school"),
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    let pm_config = ProjectConfig::load(
+        home_dir_path
+            .join("school/hw4")
+            .join(ProjectConfig::PROJECT_ROOT_REL_PATH)
+            .to_str()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pm_config.project_type.as_deref(), Some("school"));
+    assert!(pm_config
+        .opener
+        .as_deref()
+        .unwrap()
+        .ends_with("term_opener.lua"));
+    assert!(alias_group_path.join("code/hw4").exists());
+    assert!(alias_group_path.join("current-term/hw4").exists());
+
+    // projects in other libraries are not affected
+    create_project(
+        "other",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    assert!(!alias_group_path.join("current-term/other").exists());
+}
+
+#[test]
+fn test_library_ignore_rules() {
+    let unique_name = "test_library_ignore_rules";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let lib_path = gen_test_home_path(unique_name).join("lib");
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
+    create_project(
+        "proj",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    for dir in [".git", ".DS_Store", "scratch-1", "tmp", "other"] {
+        std::fs::create_dir_all(lib_path.join(dir)).unwrap();
+    }
+    std::fs::write(lib_path.join(".donnaignore"), "# scratch space\nscratch*\n").unwrap();
+    set_ignore_patterns(Some(vec!["tmp".to_string()]), &xdg).unwrap();
+    assert!(set_ignore_patterns(Some(vec!["[".to_string()]), &xdg).is_err());
+
+    let mut dirs = get_library_dirs("lib", &xdg).unwrap();
+    dirs.sort();
+    assert_eq!(dirs, vec![lib_path.join("other"), lib_path.join("proj")]);
+
+    let projects = get_projects(&xdg).unwrap();
+    assert!(projects.contains_key("proj"));
+    assert!(!projects.contains_key("other"));
+
+    assert_eq!(
+        doctor(&xdg).unwrap(),
+        vec![HealthIssue::UntrackedProjectDir {
+            lib: "lib".to_string(),
+            path: lib_path.join("other"),
+        }]
+    );
+}
+
+#[test]
+fn test_offline_library() {
+    let unique_name = "test_offline_library";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let drive_path = home_dir_path.join("drive");
+    let alias_group_path = gen_test_alias_groups_path(unique_name).join("group");
+    define_project_type("type", None, None, None, false, &xdg).unwrap();
+    create_alias_group("group", alias_group_path.to_str().unwrap(), false, &xdg).unwrap();
+    create_lib(
+        "drive",
+        drive_path.to_str().unwrap(),
+        false,
+        false,
+        None,
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "local",
+        None,
+        None,
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "remote",
+        Some("type"),
+        Some(&["group"]),
+        Some("drive"),
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    // simulate unmounting the drive
+    std::fs::rename(&drive_path, home_dir_path.join("unmounted")).unwrap();
+
+    assert_eq!(get_offline_libraries(&xdg).unwrap(), vec!["drive"]);
+    let projects = get_projects(&xdg).unwrap();
+    assert!(projects.contains_key("local"));
+    assert!(!projects.contains_key("remote"));
+    assert_eq!(
+        doctor(&xdg).unwrap(),
+        vec![HealthIssue::LibraryOffline {
+            lib: "drive".to_string(),
+            path: drive_path.clone(),
+        }]
+    );
+    assert!(create_project(
+        "new",
+        None,
+        None,
+        Some("drive"),
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg
+    )
+    .is_err());
+    assert!(!drive_path.exists());
+    untrack_project_type("type", &xdg).unwrap();
+}
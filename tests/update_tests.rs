@@ -1,6 +1,10 @@
 use donna::{
-    create_alias_group, delete_alias_group, untrack_alias_group, update_alias_group, Config, XDG,
+    create_alias_group, create_lib, create_project, delete_alias_group, delete_library, doctor,
+    errors::ProjectConfigError, get_library_aliases, migrate, repair_alias_links,
+    set_relative_links, untrack_alias_group, update_alias_group, update_library, Config,
+    HealthIssue, ProjectConfig, ScriptArgs, XDG,
 };
+use std::fs;
 
 mod utils;
 use utils::{gen_test_home_path, setup_home};
@@ -155,3 +159,298 @@ fn test_delete_alias_group() {
         "The alias group2 does not exist in the config"
     );
 }
+
+#[test]
+fn test_relative_links() {
+    let unique_name = "test_relative_links";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let group1_path = home_dir_path.join("group1");
+    create_alias_group("group1", group1_path.to_str().unwrap(), false, &xdg).unwrap();
+    set_relative_links(Some(true), Some("group1"), &xdg).unwrap();
+    create_project(
+        "proj",
+        None,
+        Some(&["group1"]),
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    let link = group1_path.join("proj");
+    assert!(fs::read_link(&link).unwrap().is_relative());
+    assert!(link.join(".pm/project.toml").exists());
+    assert!(doctor(&xdg).unwrap().is_empty());
+
+    // relative links must survive moving the group to a different depth
+    let group2_path = home_dir_path.join("nested/group2");
+    fs::create_dir_all(group2_path.parent().unwrap()).unwrap();
+    update_alias_group("group1", None, Some(group2_path.to_str().unwrap()), &xdg).unwrap();
+    let link = group2_path.join("proj");
+    assert!(fs::read_link(&link).unwrap().is_relative());
+    assert!(link.join(".pm/project.toml").exists());
+
+    // convert to absolute and back
+    set_relative_links(None, Some("group1"), &xdg).unwrap();
+    assert_eq!(
+        doctor(&xdg).unwrap(),
+        vec![HealthIssue::LinkStyleMismatch {
+            alias_group: "group1".to_string(),
+            link: link.clone(),
+            expected_relative: false,
+        }]
+    );
+    assert_eq!(repair_alias_links(None, None, &xdg).unwrap(), 1);
+    assert!(fs::read_link(&link).unwrap().is_absolute());
+    assert_eq!(
+        repair_alias_links(Some("group1"), Some(true), &xdg).unwrap(),
+        1
+    );
+    assert!(fs::read_link(&link).unwrap().is_relative());
+    assert!(link.join(".pm/project.toml").exists());
+}
+
+#[test]
+fn test_update_library() {
+    let unique_name = "test_update_library";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let lib_path = home_dir_path.join("lib1");
+    create_lib("lib1", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
+    create_alias_group(
+        "abs",
+        home_dir_path.join("abs").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    create_alias_group(
+        "rel",
+        home_dir_path.join("rel").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    set_relative_links(Some(true), Some("rel"), &xdg).unwrap();
+    create_project(
+        "proj",
+        None,
+        Some(&["abs", "rel"]),
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    // refuse to move onto an existing path
+    let taken_path = home_dir_path.join("taken");
+    fs::create_dir_all(&taken_path).unwrap();
+    assert!(update_library("lib1", None, Some(taken_path.to_str().unwrap()), &xdg).is_err());
+    assert!(lib_path.join("proj").exists());
+
+    let new_lib_path = home_dir_path.join("moved/lib2");
+    fs::create_dir_all(new_lib_path.parent().unwrap()).unwrap();
+    update_library(
+        "lib1",
+        Some("lib2"),
+        Some(new_lib_path.to_str().unwrap()),
+        &xdg,
+    )
+    .unwrap();
+
+    assert!(!lib_path.exists());
+    assert!(new_lib_path.join("proj/.pm/project.toml").exists());
+    assert!(home_dir_path.join("abs/proj/.pm/project.toml").exists());
+    assert!(home_dir_path.join("rel/proj/.pm/project.toml").exists());
+    assert!(fs::read_link(home_dir_path.join("rel/proj"))
+        .unwrap()
+        .is_relative());
+    assert!(doctor(&xdg).unwrap().is_empty());
+
+    let config = Config::load(None, &xdg).unwrap();
+    assert!(config.get_lib_path(Some("lib1")).is_none());
+    assert_eq!(config.get_lib_path(Some("lib2")), new_lib_path.to_str());
+    assert_eq!(config.get_default_lib().as_deref(), Some("lib2"));
+}
+
+#[test]
+fn test_delete_library() {
+    let unique_name = "test_delete_library";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let lib_path = home_dir_path.join("lib1");
+    let group_path = home_dir_path.join("group");
+    create_lib("lib1", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
+    create_alias_group("group", group_path.to_str().unwrap(), false, &xdg).unwrap();
+    create_project(
+        "proj1",
+        None,
+        Some(&["group"]),
+        None,
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+    create_project(
+        "proj2",
+        None,
+        Some(&["group"]),
+        Some("default"),
+        false,
+        None,
+        &ScriptArgs::default(),
+        &xdg,
+    )
+    .unwrap();
+
+    assert_eq!(
+        get_library_aliases("lib1", &xdg).unwrap(),
+        vec![group_path.join("proj1")]
+    );
+
+    delete_library("lib1", &xdg).unwrap();
+
+    assert!(!lib_path.exists());
+    assert!(fs::symlink_metadata(group_path.join("proj1")).is_err());
+    assert!(group_path.join("proj2/.pm/project.toml").exists());
+    let config = Config::load(None, &xdg).unwrap();
+    assert!(config.get_lib_path(Some("lib1")).is_none());
+    assert!(config.get_default_lib().is_none());
+}
+
+#[test]
+fn test_migrate_old_configs() {
+    let unique_name = "test_migrate_old_configs";
+    let unique_config_home_name = unique_name.to_string() + "_config";
+    let unique_data_home_name = unique_name.to_string() + "_data";
+    let xdg = XDG::new(
+        Some(unique_name),
+        Some(&unique_config_home_name),
+        Some(&unique_data_home_name),
+    );
+    let _cleanup = setup_home(unique_name, &xdg);
+
+    let home_dir_path = gen_test_home_path(unique_name);
+    let lib_path = home_dir_path.join("lib");
+    create_lib("lib", lib_path.to_str().unwrap(), true, false, None, &xdg).unwrap();
+
+    // a project config written before alias groups were tracked
+    let project_config_path = lib_path.join("old_project/.pm/project.toml");
+    fs::create_dir_all(project_config_path.parent().unwrap()).unwrap();
+    fs::write(&project_config_path, "project_type = \"rust\"\n").unwrap();
+
+    // an unversioned config
+    let config_path = Config::get_path(&xdg);
+    let old_config = fs::read_to_string(&config_path)
+        .unwrap()
+        .replace("version = 1\n", "");
+    fs::write(&config_path, &old_config).unwrap();
+
+    // reading old files migrates them in memory without writing them
+    let project_config = ProjectConfig::load(project_config_path.to_str().unwrap()).unwrap();
+    assert_eq!(project_config.tracked_alias_groups, Some(vec![]));
+    Config::load(None, &xdg).unwrap();
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), old_config);
+    assert_eq!(
+        fs::read_to_string(&project_config_path).unwrap(),
+        "project_type = \"rust\"\n"
+    );
+
+    let migrated = migrate(true, &xdg).unwrap();
+    assert_eq!(migrated.len(), 2, "Both files should need migrating");
+    assert_eq!(
+        fs::read_to_string(&project_config_path).unwrap(),
+        "project_type = \"rust\"\n",
+        "Check should not modify files"
+    );
+
+    let migrated = migrate(false, &xdg).unwrap();
+    assert_eq!(migrated.len(), 2, "Both files should have been migrated");
+    let project_config = ProjectConfig::load(project_config_path.to_str().unwrap()).unwrap();
+    assert_eq!(project_config.version, Some(1));
+    assert_eq!(project_config.tracked_alias_groups, Some(vec![]));
+    assert_eq!(
+        fs::read_to_string(lib_path.join("old_project/.pm/project.toml.v0.bak")).unwrap(),
+        "project_type = \"rust\"\n",
+        "The original project config should be backed up"
+    );
+    assert_eq!(
+        fs::read_to_string(config_path.with_file_name("config.toml.v0.bak")).unwrap(),
+        old_config,
+        "The original config should be backed up"
+    );
+    assert!(
+        migrate(true, &xdg).unwrap().is_empty(),
+        "Nothing should need migrating after a migration"
+    );
+
+    // configs from a newer version of donna are refused rather than overwritten
+    fs::write(&project_config_path, "version = 99\n").unwrap();
+    assert!(matches!(
+        ProjectConfig::load(project_config_path.to_str().unwrap()),
+        Err(ProjectConfigError::UnsupportedVersion(99))
+    ));
+
+    // versions that don't fit a layout version aren't wrapped around
+    for version in ["-1", "4294967296"] {
+        fs::write(&project_config_path, format!("version = {version}\n")).unwrap();
+        assert!(matches!(
+            ProjectConfig::load(project_config_path.to_str().unwrap()),
+            Err(ProjectConfigError::InvalidVersion(_))
+        ));
+    }
+    assert_eq!(
+        fs::read_to_string(&project_config_path).unwrap(),
+        "version = 4294967296\n",
+        "Files with an invalid version should not be migrated"
+    );
+
+    // an old config is backed up when a change is saved over it
+    fs::remove_file(config_path.with_file_name("config.toml.v0.bak")).unwrap();
+    fs::write(&config_path, &old_config).unwrap();
+    create_alias_group(
+        "group",
+        home_dir_path.join("group").to_str().unwrap(),
+        false,
+        &xdg,
+    )
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(config_path.with_file_name("config.toml.v0.bak")).unwrap(),
+        old_config
+    );
+    assert!(fs::read_to_string(&config_path)
+        .unwrap()
+        .contains("version = 1"));
+}
@@ -91,7 +91,7 @@ pub fn delete_home(unique_name: &str) {
 
 pub fn setup_home(unique_name: &str, xdg: &XDG) -> TestDir {
     set_home_env(unique_name, xdg);
-    setup_pm(xdg);
+    setup_pm(xdg).unwrap();
     TestDir::mark(gen_test_home_path(unique_name).to_str().unwrap())
 }
 